@@ -0,0 +1,169 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::error::{ParseError, SolveError};
+use crate::solver::{Part, Puzzle};
+
+/// How many times to run each step of a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchOptions {
+    /// Runs whose times are thrown away, to warm up caches and the allocator.
+    pub warmup: usize,
+    /// Timed runs. Must be at least 1.
+    pub runs: usize,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions {
+            warmup: 3,
+            runs: 20,
+        }
+    }
+}
+
+/// Summary statistics over a set of timed runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    /// The population standard deviation.
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Returns `None` if there are no samples.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+
+        let seconds: Vec<f64> = sorted.iter().map(Duration::as_secs_f64).collect();
+        let mean = seconds.iter().sum::<f64>() / seconds.len() as f64;
+        let variance =
+            seconds.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / seconds.len() as f64;
+
+        Some(Stats {
+            runs: sorted.len(),
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+/// The benchmark results of a single day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayBench {
+    pub day: u32,
+    pub parse: Stats,
+    /// The solve time of each part. Parts that failed hold the error instead.
+    pub parts: Vec<(Part, Result<Stats, SolveError>)>,
+}
+
+fn time_runs<T, F: FnMut() -> T>(options: &BenchOptions, mut f: F) -> Vec<Duration> {
+    for _ in 0..options.warmup {
+        black_box(f());
+    }
+
+    (0..options.runs.max(1))
+        .map(|_| {
+            let now = Instant::now();
+            black_box(f());
+            now.elapsed()
+        })
+        .collect()
+}
+
+/// Benchmarks parsing `input` and solving both parts of `puzzle`, timing
+/// each step separately.
+pub fn bench_puzzle(
+    puzzle: &dyn Puzzle,
+    input: &str,
+    options: &BenchOptions,
+) -> Result<DayBench, ParseError> {
+    // parse once up front, so that parse errors are reported before timing
+    let parsed = puzzle.parse(input)?;
+    let parse_samples = time_runs(options, || puzzle.parse(input));
+
+    let parts = Part::BOTH
+        .into_iter()
+        .map(|part| {
+            let stats = puzzle.solve(parsed.as_ref(), part).map(|_| {
+                let samples = time_runs(options, || puzzle.solve(parsed.as_ref(), part));
+                Stats::from_samples(&samples).unwrap()
+            });
+            (part, stats)
+        })
+        .collect();
+
+    Ok(DayBench {
+        day: puzzle.day(),
+        parse: Stats::from_samples(&parse_samples).unwrap(),
+        parts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(samples: &[u64]) -> Vec<Duration> {
+        samples
+            .iter()
+            .map(|&ms| Duration::from_millis(ms))
+            .collect()
+    }
+
+    #[test]
+    fn no_samples() {
+        assert_eq!(Stats::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn single_sample() {
+        let stats = Stats::from_samples(&millis(&[7])).unwrap();
+        assert_eq!(stats.runs, 1);
+        assert_eq!(stats.min, Duration::from_millis(7));
+        assert_eq!(stats.median, Duration::from_millis(7));
+        assert_eq!(stats.mean, Duration::from_millis(7));
+        assert_eq!(stats.stddev, Duration::ZERO);
+    }
+
+    #[test]
+    fn odd_number_of_samples() {
+        let stats = Stats::from_samples(&millis(&[9, 1, 5])).unwrap();
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn even_number_of_samples() {
+        // the median is the mean of the two middle samples
+        let stats = Stats::from_samples(&millis(&[8, 2, 4, 6])).unwrap();
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.median, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn mean_and_stddev() {
+        // the classic example with a population standard deviation of 2
+        let stats = Stats::from_samples(&millis(&[2, 4, 4, 4, 5, 5, 7, 9])).unwrap();
+        let close = |a: Duration, b: Duration| a.abs_diff(b) < Duration::from_nanos(10);
+        assert!(close(stats.mean, Duration::from_millis(5)));
+        assert!(close(stats.stddev, Duration::from_millis(2)));
+    }
+}
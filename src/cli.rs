@@ -0,0 +1,480 @@
+use std::path::PathBuf;
+
+use aoc2023::bench::BenchOptions;
+use aoc2023::manifest::DEFAULT_MANIFEST_PATH;
+use aoc2023::{pool, Part};
+
+pub const USAGE: &str = "\
+Usage: aoc2023 [--format <text|json>] [COMMAND]
+
+Commands:
+  (none) [--jobs <n>]    Run every day on its full input
+  <day>                  Run both parts of a day on its full input
+  run <day> [OPTIONS]    Run a single day
+  verify [--manifest <path>] [--jobs <n>]
+                         Check every answer in the manifest (default: answers.toml)
+  bench [<day>...] [OPTIONS]
+                         Time parsing and solving of the given days (default: all)
+  cards [OPTIONS]        Score Camel Cards hands (day 7) under custom rules
+  network [OPTIONS]      Export the network of day 8 as a Graphviz DOT graph
+  list                   List the available days
+  help                   Print this message
+
+Global options:
+  --format <text|json>   Print results as text (default), or as one JSON record
+                         per day and part. Only for running days and verify
+
+Options for running every day and verify:
+  --jobs <n>             Solve days and parts on <n> threads, or one per core if
+                         <n> is 0. Results are printed in day order (default: 1)
+
+Options for run:
+  --part <1|2>           Only run the given part
+  --input <path>         Read the puzzle input from a file, or stdin if <path> is -
+  --stdin                Read the puzzle input from stdin
+
+Options for cards:
+  --rules <path>         Load the ruleset from a TOML file, see src/d7/house_rules.toml
+                         (default: the rules of part one)
+  --input <path>         Read the hands from a file, or stdin if <path> is -
+  --stdin                Read the hands from stdin
+  --table                Print every hand with its rank, type and winnings
+
+Options for network:
+  --dot <path>           Write the graph to a file, or stdout if <path> is - (default: -)
+  --paths                Also draw the path of each ghost
+  --input <path>         Read the network from a file, or stdin if <path> is -
+  --stdin                Read the network from stdin
+
+Options for bench:
+  --runs <n>             Number of timed runs of each step (default: 20)
+  --warmup <n>           Number of untimed runs before timing (default: 3)";
+
+/// Where to read a puzzle input from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    /// The full input stored next to the solver.
+    Default,
+    File(PathBuf),
+    Stdin,
+}
+
+/// How to print the results of running or verifying puzzles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    /// One JSON object per line, for each day and part.
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    RunAll {
+        jobs: usize,
+    },
+    Run {
+        day: u32,
+        part: Option<Part>,
+        input: InputSource,
+    },
+    Verify {
+        manifest: PathBuf,
+        jobs: usize,
+    },
+    Bench {
+        /// The days to benchmark, all days if empty.
+        days: Vec<u32>,
+        options: BenchOptions,
+    },
+    Cards {
+        /// The ruleset file, the part one rules if not given.
+        rules: Option<PathBuf>,
+        input: InputSource,
+        /// Print the ranked hands, not just the total winnings.
+        table: bool,
+    },
+    Network {
+        /// Where to write the DOT graph, stdout if not given.
+        dot: Option<PathBuf>,
+        /// Draw the path of each ghost.
+        paths: bool,
+        input: InputSource,
+    },
+    List,
+    Help,
+}
+
+impl Command {
+    /// Whether the command prints results that `--format` applies to.
+    fn takes_format(&self) -> bool {
+        matches!(
+            self,
+            Command::RunAll { .. } | Command::Run { .. } | Command::Verify { .. } | Command::Help
+        )
+    }
+}
+
+fn parse_day(string: &str) -> Result<u32, String> {
+    string
+        .parse()
+        .map_err(|_| format!("Could not parse day as int: {:?}", string))
+}
+
+fn parse_run(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter();
+
+    let day = parse_day(args.next().ok_or("Missing day for run")?)?;
+    let mut part = None;
+    let mut input = InputSource::Default;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--part" | "-p" => {
+                let value = args.next().ok_or("Missing value for --part")?;
+                part = Some(
+                    Part::from_number(value)
+                        .ok_or_else(|| format!("Part must be 1 or 2, got {:?}", value))?,
+                );
+            }
+            "--input" | "-i" => input = parse_input(args.next())?,
+            "--stdin" => input = InputSource::Stdin,
+            other => return Err(format!("Unknown argument: {:?}", other)),
+        }
+    }
+
+    Ok(Command::Run { day, part, input })
+}
+
+fn parse_input(value: Option<&String>) -> Result<InputSource, String> {
+    match value.ok_or("Missing value for --input")?.as_str() {
+        "-" => Ok(InputSource::Stdin),
+        path => Ok(InputSource::File(PathBuf::from(path))),
+    }
+}
+
+fn parse_cards(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter();
+    let mut rules = None;
+    let mut input = InputSource::Default;
+    let mut table = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rules" | "-r" => {
+                rules = Some(PathBuf::from(
+                    args.next().ok_or("Missing value for --rules")?,
+                ));
+            }
+            "--input" | "-i" => input = parse_input(args.next())?,
+            "--stdin" => input = InputSource::Stdin,
+            "--table" | "-t" => table = true,
+            other => return Err(format!("Unknown argument: {:?}", other)),
+        }
+    }
+
+    Ok(Command::Cards {
+        rules,
+        input,
+        table,
+    })
+}
+
+fn parse_network(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter();
+    let mut dot = None;
+    let mut paths = false;
+    let mut input = InputSource::Default;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dot" | "-d" => {
+                dot = match args.next().ok_or("Missing value for --dot")?.as_str() {
+                    "-" => None,
+                    path => Some(PathBuf::from(path)),
+                };
+            }
+            "--paths" => paths = true,
+            "--input" | "-i" => input = parse_input(args.next())?,
+            "--stdin" => input = InputSource::Stdin,
+            other => return Err(format!("Unknown argument: {:?}", other)),
+        }
+    }
+
+    Ok(Command::Network { dot, paths, input })
+}
+
+fn parse_run_all(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter();
+    let mut jobs = 1;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--jobs" | "-j" => jobs = parse_jobs(args.next())?,
+            other => return Err(format!("Unknown argument: {:?}", other)),
+        }
+    }
+
+    Ok(Command::RunAll { jobs })
+}
+
+fn parse_verify(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter();
+    let mut manifest = PathBuf::from(DEFAULT_MANIFEST_PATH);
+    let mut jobs = 1;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--manifest" | "-m" => {
+                manifest = PathBuf::from(args.next().ok_or("Missing value for --manifest")?);
+            }
+            "--jobs" | "-j" => jobs = parse_jobs(args.next())?,
+            other => return Err(format!("Unknown argument: {:?}", other)),
+        }
+    }
+
+    Ok(Command::Verify { manifest, jobs })
+}
+
+fn parse_count(flag: &str, value: Option<&String>) -> Result<usize, String> {
+    let value = value.ok_or_else(|| format!("Missing value for {}", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("Could not parse {} as int: {:?}", flag, value))
+}
+
+/// Parses the value of `--jobs`, where 0 means one job per core.
+fn parse_jobs(value: Option<&String>) -> Result<usize, String> {
+    match parse_count("--jobs", value)? {
+        0 => Ok(pool::default_workers()),
+        jobs => Ok(jobs),
+    }
+}
+
+fn parse_bench(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter();
+
+    let mut days = vec![];
+    let mut options = BenchOptions::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" | "-n" => options.runs = parse_count("--runs", args.next())?,
+            "--warmup" | "-w" => options.warmup = parse_count("--warmup", args.next())?,
+            day => days.push(parse_day(day)?),
+        }
+    }
+
+    if options.runs == 0 {
+        return Err("--runs must be at least 1".to_owned());
+    }
+
+    Ok(Command::Bench { days, options })
+}
+
+/// Removes the `--format` option from `args`, wherever it is. Returns `None`
+/// if it is not given.
+fn take_format(args: &mut Vec<String>) -> Result<Option<Format>, String> {
+    let Some(index) = args.iter().position(|arg| arg == "--format" || arg == "-f") else {
+        return Ok(None);
+    };
+    let value = args
+        .get(index + 1)
+        .ok_or("Missing value for --format")?
+        .to_owned();
+    args.drain(index..index + 2);
+
+    match value.as_str() {
+        "text" => Ok(Some(Format::Text)),
+        "json" => Ok(Some(Format::Json)),
+        other => Err(format!("Format must be text or json, got {:?}", other)),
+    }
+}
+
+/// Parses the command line arguments, not including the program name.
+pub fn parse_args(args: &[String]) -> Result<(Command, Format), String> {
+    let mut args = args.to_vec();
+    let format = take_format(&mut args)?;
+    let command = parse_command(&args)?;
+
+    if format.is_some() && !command.takes_format() {
+        return Err(format!(
+            "--format only applies to running days and verify, not {:?}",
+            args[0]
+        ));
+    }
+    Ok((command, format.unwrap_or_default()))
+}
+
+fn parse_command(args: &[String]) -> Result<Command, String> {
+    match args.first().map(String::as_str) {
+        None => Ok(Command::RunAll { jobs: 1 }),
+        Some("--jobs" | "-j") => parse_run_all(args),
+        Some("run") => parse_run(&args[1..]),
+        Some("verify") => parse_verify(&args[1..]),
+        Some("bench") => parse_bench(&args[1..]),
+        Some("cards") => parse_cards(&args[1..]),
+        Some("network") => parse_network(&args[1..]),
+        Some("list") if args.len() == 1 => Ok(Command::List),
+        Some("help" | "--help" | "-h") => Ok(Command::Help),
+        Some(day) if args.len() == 1 => Ok(Command::Run {
+            day: parse_day(day)?,
+            part: None,
+            input: InputSource::Default,
+        }),
+        Some(_) => Err("Got too many command line arguments!".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<(Command, Format), String> {
+        let args: Vec<String> = args.split_whitespace().map(str::to_owned).collect();
+        parse_args(&args)
+    }
+
+    fn command(args: &str) -> Command {
+        parse(args).unwrap().0
+    }
+
+    #[test]
+    fn no_arguments() {
+        assert_eq!(command(""), Command::RunAll { jobs: 1 });
+    }
+
+    #[test]
+    fn bare_day() {
+        assert_eq!(
+            command("5"),
+            Command::Run {
+                day: 5,
+                part: None,
+                input: InputSource::Default
+            }
+        );
+        assert!(parse("five").is_err());
+        assert!(parse("5 6").is_err());
+    }
+
+    #[test]
+    fn run_options() {
+        assert_eq!(
+            command("run 3 --part 2 --input -"),
+            Command::Run {
+                day: 3,
+                part: Some(Part::Two),
+                input: InputSource::Stdin
+            }
+        );
+        assert_eq!(
+            command("run 3 -i some/file.txt"),
+            Command::Run {
+                day: 3,
+                part: None,
+                input: InputSource::File(PathBuf::from("some/file.txt"))
+            }
+        );
+        assert_eq!(
+            command("run 3 --stdin"),
+            Command::Run {
+                day: 3,
+                part: None,
+                input: InputSource::Stdin
+            }
+        );
+    }
+
+    #[test]
+    fn bad_part() {
+        let err = parse("run 3 --part 3").unwrap_err();
+        assert_eq!(err, "Part must be 1 or 2, got \"3\"");
+    }
+
+    #[test]
+    fn missing_values() {
+        assert_eq!(
+            parse("run 3 --part").unwrap_err(),
+            "Missing value for --part"
+        );
+        assert_eq!(
+            parse("run 3 --input").unwrap_err(),
+            "Missing value for --input"
+        );
+        assert_eq!(parse("--jobs").unwrap_err(), "Missing value for --jobs");
+        assert_eq!(parse("run").unwrap_err(), "Missing day for run");
+        assert_eq!(
+            parse("5 --format").unwrap_err(),
+            "Missing value for --format"
+        );
+    }
+
+    #[test]
+    fn format_anywhere() {
+        let expected = Command::Verify {
+            manifest: PathBuf::from(DEFAULT_MANIFEST_PATH),
+            jobs: 1,
+        };
+        assert_eq!(
+            parse("--format json verify"),
+            Ok((expected.clone(), Format::Json))
+        );
+        assert_eq!(
+            parse("verify -f json"),
+            Ok((expected.clone(), Format::Json))
+        );
+        assert_eq!(parse("verify --format text"), Ok((expected, Format::Text)));
+        assert!(parse("verify --format yaml").is_err());
+        assert_eq!(
+            parse("bench 1 --format json").unwrap_err(),
+            "--format only applies to running days and verify, not \"bench\""
+        );
+        assert!(parse("--format text cards").is_err());
+        assert!(parse("network -f json").is_err());
+        assert!(parse("list --format json").is_err());
+    }
+
+    #[test]
+    fn jobs() {
+        assert_eq!(
+            command("--jobs 0"),
+            Command::RunAll {
+                jobs: pool::default_workers()
+            }
+        );
+        assert_eq!(command("-j 3"), Command::RunAll { jobs: 3 });
+        assert_eq!(
+            command("verify --jobs 0 --manifest other.toml"),
+            Command::Verify {
+                manifest: PathBuf::from("other.toml"),
+                jobs: pool::default_workers()
+            }
+        );
+        assert!(parse("--jobs many").is_err());
+    }
+
+    #[test]
+    fn bench_options() {
+        assert_eq!(
+            command("bench 1 4 --runs 5 -w 0"),
+            Command::Bench {
+                days: vec![1, 4],
+                options: BenchOptions { warmup: 0, runs: 5 }
+            }
+        );
+        assert_eq!(
+            parse("bench --runs 0").unwrap_err(),
+            "--runs must be at least 1"
+        );
+    }
+
+    #[test]
+    fn unknown_arguments() {
+        assert_eq!(
+            parse("run 3 --fast").unwrap_err(),
+            "Unknown argument: \"--fast\""
+        );
+        assert!(parse("cards --table --verbose").is_err());
+    }
+}
@@ -1,29 +1,21 @@
-use std::time::Instant;
-
-use crate::utils;
-
-fn find_num<'a, T: Iterator<Item = &'a u8>>(bytes: T) -> Option<u8> {
-    for byte in bytes {
-        let num = *byte;
-        if num.is_ascii_digit() {
-            return Some(num - b'0');
-        }
-    }
-
-    None
+use crate::error::{ParseError, ParseErrorKind, SolveError};
+use crate::solver::{Answer, Solver};
+
+/// The digits in one line of the calibration document, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalibrationLine {
+    /// The digits written as digits.
+    pub digits: Vec<u8>,
+    /// The digits written either as digits or spelled out, like `one`.
+    pub spelled_digits: Vec<u8>,
 }
 
-fn get_sum_p1(filename: &str) -> u32 {
-    let mut calibration_sum: u32 = 0;
-    for line in utils::read_lines(filename) {
-        let bytes = line.as_bytes();
-
-        let left_num = find_num(bytes.iter()).unwrap() as u32;
-        let right_num = find_num(bytes.iter().rev()).unwrap() as u32;
-
-        calibration_sum += left_num * 10 + right_num;
-    }
-    calibration_sum
+fn no_digit_error(input: &str, line: &str) -> ParseError {
+    ParseError::at(
+        input,
+        line,
+        ParseErrorKind::Expected("a line containing a digit"),
+    )
 }
 
 macro_rules! test_string {
@@ -39,74 +31,150 @@ macro_rules! test_string {
     };
 }
 
-fn get_sum_p2(filename: &str) -> u32 {
-    let mut calibration_sum: u32 = 0;
-
-    for line in utils::read_lines(filename) {
-        let bytes: Vec<u8> = line.as_bytes().into();
+fn find_spelled_digits(bytes: &[u8]) -> Vec<u8> {
+    let mut numbers: Vec<u8> = vec![];
 
-        let mut has_itered = false;
-        let mut idx = 0;
+    for idx in 0..bytes.len() {
+        if bytes[idx] >= b'0' && bytes[idx] <= b'9' {
+            numbers.push(bytes[idx] - b'0');
+            continue;
+        }
 
-        let mut numbers: Vec<u8> = vec![];
+        let mut small_vec: Vec<u8> = vec![];
 
-        while idx + 1 < bytes.len() {
-            if has_itered {
-                idx += 1;
-            } else {
-                has_itered = true;
-            }
+        if idx + 2 < bytes.len() {
+            small_vec = [bytes[idx], bytes[idx + 1], bytes[idx + 2]].to_vec();
+            test_string!(numbers, small_vec; "one": 1, "two": 2, "six": 6);
+        }
 
-            if bytes[idx] >= b'0' && bytes[idx] <= b'9' {
-                numbers.push(bytes[idx] - b'0');
-                continue;
-            }
+        if idx + 3 < bytes.len() {
+            small_vec.push(bytes[idx + 3]);
+            test_string!(numbers, small_vec; "four": 4, "five": 5, "nine": 9);
+        }
 
-            let mut small_vec: Vec<u8> = vec![];
+        if idx + 4 < bytes.len() {
+            small_vec.push(bytes[idx + 4]);
+            test_string!(numbers, small_vec; "three": 3, "seven": 7, "eight": 8);
+        }
+    }
 
-            if idx + 2 < bytes.len() {
-                small_vec = [bytes[idx], bytes[idx + 1], bytes[idx + 2]].to_vec();
-                test_string!(numbers, small_vec; "one": 1, "two": 2, "six": 6);
-            }
+    numbers
+}
 
-            if idx + 3 < bytes.len() {
-                small_vec.push(bytes[idx + 3]);
-                test_string!(numbers, small_vec; "four": 4, "five": 5, "nine": 9);
+/// Finds the digits of every line. A line must hold at least one digit,
+/// either written as a digit or spelled out.
+pub fn get_calibration_lines(input: &str) -> Result<Vec<CalibrationLine>, ParseError> {
+    input
+        .lines()
+        .map(|line| {
+            let bytes = line.as_bytes();
+            let digits: Vec<u8> = bytes
+                .iter()
+                .filter(|byte| byte.is_ascii_digit())
+                .map(|byte| byte - b'0')
+                .collect();
+            let spelled_digits = find_spelled_digits(bytes);
+            if spelled_digits.is_empty() {
+                return Err(no_digit_error(input, line));
             }
 
-            if idx + 4 < bytes.len() {
-                small_vec.push(bytes[idx + 4]);
-                test_string!(numbers, small_vec; "three": 3, "seven": 7, "eight": 8);
-            }
-        }
+            Ok(CalibrationLine {
+                digits,
+                spelled_digits,
+            })
+        })
+        .collect()
+}
 
-        let left_num = numbers[0] as u32;
-        let right_num = numbers[numbers.len() - 1] as u32;
+/// Sums the first and last digit of every line, as picked by `digits`.
+fn get_sum(
+    lines: &[CalibrationLine],
+    digits: impl Fn(&CalibrationLine) -> &[u8],
+) -> Result<u32, SolveError> {
+    let mut calibration_sum: u32 = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let digits = digits(line);
+        let (Some(&left_num), Some(&right_num)) = (digits.first(), digits.last()) else {
+            return Err(SolveError::Invalid(format!("line {} has no digit", i + 1)));
+        };
 
-        calibration_sum += left_num * 10 + right_num;
+        calibration_sum += left_num as u32 * 10 + right_num as u32;
     }
+    Ok(calibration_sum)
+}
 
-    calibration_sum
+pub fn get_sum_p1(lines: &[CalibrationLine]) -> Result<u32, SolveError> {
+    get_sum(lines, |line| &line.digits)
 }
 
-fn test() {
-    assert_eq!(get_sum_p1("src/d1/test_input_p1.dat"), 142);
-    assert_eq!(get_sum_p2("src/d1/test_input_p2.dat"), 281);
+pub fn get_sum_p2(lines: &[CalibrationLine]) -> Result<u32, SolveError> {
+    get_sum(lines, |line| &line.spelled_digits)
 }
 
-pub fn test_final() {
-    assert_eq!(get_sum_p1("src/d1/full_input.dat"), 54968);
-    assert_eq!(get_sum_p2("src/d1/full_input.dat"), 54094);
+pub struct Day1;
+
+impl Solver for Day1 {
+    type Input = Vec<CalibrationLine>;
+
+    const DAY: u32 = 1;
+    const TITLE: &'static str = "Trebuchet?!";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        get_calibration_lines(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(get_sum_p1(input)?.into())
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(get_sum_p2(input)?.into())
+    }
 }
 
-pub fn main() {
-    test();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
 
-    let mut now = Instant::now();
-    let sum_p1 = get_sum_p1("src/d1/full_input.dat");
-    println!("Part one result: {} (took {:?})", sum_p1, now.elapsed());
+    fn sum_p1(input: &str) -> Result<u32, SolveError> {
+        get_sum_p1(&get_calibration_lines(input)?)
+    }
+
+    fn sum_p2(input: &str) -> Result<u32, SolveError> {
+        get_sum_p2(&get_calibration_lines(input)?)
+    }
+
+    #[test]
+    fn part_one_example() {
+        let input = utils::read_input("src/d1/test_input_p1.dat");
+        assert_eq!(sum_p1(&input), Ok(142));
+    }
 
-    now = Instant::now();
-    let sum_p2 = get_sum_p2("src/d1/full_input.dat");
-    println!("Part two result: {} (took {:?})", sum_p2, now.elapsed());
+    #[test]
+    fn part_two_example() {
+        let input = utils::read_input("src/d1/test_input_p2.dat");
+        assert_eq!(sum_p2(&input), Ok(281));
+    }
+
+    #[test]
+    fn last_character_is_checked() {
+        assert_eq!(sum_p2("7"), Ok(77));
+        assert_eq!(sum_p2("ab1"), Ok(11));
+        assert_eq!(sum_p2("xtwo"), Ok(22));
+    }
+
+    #[test]
+    fn line_without_digit() {
+        let err = get_calibration_lines("1abc2\nabc\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(err.text, "abc");
+
+        // only spelled out digits are fine for part two, but not part one
+        assert_eq!(sum_p2("1abc2\nabcone"), Ok(12 + 11));
+        assert!(matches!(
+            sum_p1("1abc2\nabcone"),
+            Err(SolveError::Invalid(_))
+        ));
+    }
 }
@@ -1,104 +1,150 @@
 use regex::Regex;
 use std::cmp::max;
-use std::collections::HashMap;
-use std::time::Instant;
-
-use crate::utils;
-
-fn is_possible(line: &str, regex: &Regex, max_map: &HashMap<&str, u32>) -> bool {
-    for count_and_color in regex
-        .captures_iter(line)
-        .flat_map(|c| c.get(0).unwrap().as_str().split(", "))
-        .map(|v| v.split(' ').collect::<Vec<&str>>())
-    {
-        match count_and_color[..] {
-            [count_str, color] => {
-                if count_str.parse::<u32>().unwrap() > max_map[color] {
-                    return false;
+
+use crate::error::{ParseError, ParseErrorKind, SolveError};
+use crate::solver::{Answer, Solver};
+
+/// Gets every count and color shown in a game. Errors point into 'line'.
+fn get_counts_and_colors<'a>(
+    line: &'a str,
+    regex: &Regex,
+) -> Result<Vec<(u32, &'a str)>, ParseError> {
+    regex
+        .find_iter(line)
+        .flat_map(|m| m.as_str().split(", "))
+        .map(
+            |count_and_color| match count_and_color.split(' ').collect::<Vec<&str>>()[..] {
+                [count_str, color] => {
+                    let count = count_str.parse().map_err(|_| {
+                        ParseError::at(line, count_str, ParseErrorKind::InvalidNumber)
+                    })?;
+                    Ok((count, color))
                 }
-            }
-            _ => panic!("Got malformed count_and_color: {:?}", count_and_color),
-        }
-    }
+                _ => Err(ParseError::at(
+                    line,
+                    count_and_color,
+                    ParseErrorKind::Expected("`<count> <color>`"),
+                )),
+            },
+        )
+        .collect()
+}
 
-    true
+/// A game, and the fewest cubes of each color that make it possible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Game {
+    pub id: u32,
+    pub red: u32,
+    pub green: u32,
+    pub blue: u32,
 }
 
-fn get_min_possible(line: &str, regex: &Regex) -> HashMap<String, u32> {
-    let mut map = HashMap::new();
-    for count_and_color in regex
-        .captures_iter(line)
-        .flat_map(|c| c.get(0).unwrap().as_str().split(", "))
-        .map(|v| v.split(' ').collect::<Vec<&str>>())
-    {
-        match count_and_color[..] {
-            [count_str, color] => {
-                let count: u32 = count_str.parse().unwrap();
-
-                map.entry(color.to_owned())
-                    .and_modify(|v| *v = max(count, *v))
-                    .or_insert(count);
-            }
-            _ => panic!("Got malformed count_and_color: {:?}", count_and_color),
+impl Game {
+    /// Parses a `Game <id>: <draws>` line. Errors point into 'line'.
+    fn from_string(line: &str, id_re: &Regex, game_re: &Regex) -> Result<Self, ParseError> {
+        let game_match = id_re
+            .captures(line)
+            .and_then(|c| c.get(1))
+            .ok_or_else(|| ParseError::at(line, line, ParseErrorKind::Expected("`Game <id>:`")))?;
+        let id = game_match.as_str().parse().map_err(|_| {
+            ParseError::at(line, game_match.as_str(), ParseErrorKind::InvalidNumber)
+        })?;
+
+        let mut game = Game {
+            id,
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        for (count, color) in get_counts_and_colors(line, game_re)? {
+            let min_count = match color {
+                "red" => &mut game.red,
+                "green" => &mut game.green,
+                "blue" => &mut game.blue,
+                _ => {
+                    return Err(ParseError::at(
+                        line,
+                        color,
+                        ParseErrorKind::Unknown("color"),
+                    ))
+                }
+            };
+            *min_count = max(*min_count, count);
         }
-    }
 
-    map
+        Ok(game)
+    }
 }
 
-fn count_possible(filename: &str) -> u32 {
-    let mut max_map = HashMap::new();
-    max_map.insert("red", 12);
-    max_map.insert("green", 13);
-    max_map.insert("blue", 14);
-
+pub fn get_games(input: &str) -> Result<Vec<Game>, ParseError> {
     let id_re = Regex::new(r"Game (\d+):").unwrap();
     let game_re = Regex::new(r"(?:\d+ \w+(?:, )?)+").unwrap();
 
-    let mut sum: u32 = 0;
-    for line in utils::read_lines(filename) {
-        let game_match = id_re.captures(&line).unwrap().get(1).unwrap();
-        let game_id: u32 = game_match.as_str().parse().unwrap();
+    input
+        .lines()
+        .map(|line| {
+            Game::from_string(line, &id_re, &game_re).map_err(|err| err.within(input, line))
+        })
+        .collect()
+}
 
-        if is_possible(&line, &game_re, &max_map) {
-            sum += game_id;
-        }
-    }
+pub fn count_possible(games: &[Game]) -> u32 {
+    games
+        .iter()
+        .filter(|game| game.red <= 12 && game.green <= 13 && game.blue <= 14)
+        .map(|game| game.id)
+        .sum()
+}
 
-    sum
+pub fn get_power_sum(games: &[Game]) -> u32 {
+    games
+        .iter()
+        .map(|game| game.red * game.green * game.blue)
+        .sum()
 }
 
-fn get_power_sum(filename: &str) -> u32 {
-    let game_re = Regex::new(r"(?:\d+ \w+(?:, )?)+").unwrap();
+pub struct Day2;
+
+impl Solver for Day2 {
+    type Input = Vec<Game>;
 
-    let mut sum: u32 = 0;
-    for line in utils::read_lines(filename) {
-        let min_map = get_min_possible(&line, &game_re);
+    const DAY: u32 = 2;
+    const TITLE: &'static str = "Cube Conundrum";
 
-        sum += min_map["red"] * min_map["green"] * min_map["blue"];
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        get_games(input)
     }
 
-    sum
-}
+    fn part_one(&self, games: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(count_possible(games).into())
+    }
 
-fn test() {
-    assert_eq!(count_possible("src/d2/test_input.dat"), 8);
-    assert_eq!(get_power_sum("src/d2/test_input.dat"), 2286);
+    fn part_two(&self, games: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(get_power_sum(games).into())
+    }
 }
 
-pub fn test_final() {
-    assert_eq!(count_possible("src/d2/full_input.dat"), 2505);
-    assert_eq!(get_power_sum("src/d2/full_input.dat"), 70265);
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
 
-pub fn main() {
-    test();
+    #[test]
+    fn part_one_example() {
+        let input = utils::read_input("src/d2/test_input.dat");
+        assert_eq!(count_possible(&get_games(&input).unwrap()), 8);
+    }
 
-    let mut now = Instant::now();
-    let sum_p1 = count_possible("src/d2/full_input.dat");
-    println!("Part one result: {} (took {:?})", sum_p1, now.elapsed());
+    #[test]
+    fn part_two_example() {
+        let input = utils::read_input("src/d2/test_input.dat");
+        assert_eq!(get_power_sum(&get_games(&input).unwrap()), 2286);
+    }
 
-    now = Instant::now();
-    let sum_p2 = get_power_sum("src/d2/full_input.dat");
-    println!("Part two result: {} (took {:?})", sum_p2, now.elapsed());
+    #[test]
+    fn unknown_color() {
+        let err = get_games("Game 1: 3 blue\nGame 2: 1 pink").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::Unknown("color"));
+        assert_eq!(err.line, 2);
+    }
 }
@@ -1,16 +1,42 @@
 use regex::Regex;
-use std::{collections::HashMap, time::Instant};
+use std::collections::HashMap;
 
-use crate::utils;
+use crate::error::{ParseError, ParseErrorKind, SolveError};
+use crate::solver::{Answer, Solver};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct PartNumber {
     number: u32,
     row: usize,
     range: (usize, usize),
 }
 
-fn get_part_numbers(lines: &[String]) -> Vec<PartNumber> {
+/// The engine schematic: a rectangular grid of ASCII characters, and the
+/// numbers in it.
+#[derive(Debug, Clone)]
+pub struct Schematic {
+    lines: Vec<String>,
+    part_numbers: Vec<PartNumber>,
+}
+
+/// Splits the input into lines, making sure they form a rectangular ASCII grid.
+fn get_lines(input: &str) -> Result<Vec<&str>, ParseError> {
+    let lines: Vec<&str> = input.lines().collect();
+
+    for line in lines.iter() {
+        if line.len() != lines[0].len() || !line.is_ascii() {
+            return Err(ParseError::at(
+                input,
+                line,
+                ParseErrorKind::Expected("rows of ASCII characters of equal length"),
+            ));
+        }
+    }
+
+    Ok(lines)
+}
+
+fn get_part_numbers(lines: &[&str]) -> Result<Vec<PartNumber>, ParseError> {
     let number_re = Regex::new(r"\d+").unwrap();
     lines
         .iter()
@@ -18,12 +44,16 @@ fn get_part_numbers(lines: &[String]) -> Vec<PartNumber> {
         .flat_map(|(row, line)| number_re.captures_iter(line).map(move |v| (row, v)))
         .map(|(row, c)| {
             let re_match = c.get(0).unwrap();
+            let number = re_match.as_str().parse().map_err(|_| ParseError {
+                line: row + 1,
+                ..ParseError::at(lines[row], re_match.as_str(), ParseErrorKind::InvalidNumber)
+            })?;
 
-            PartNumber {
-                number: re_match.as_str().parse().unwrap(),
+            Ok(PartNumber {
+                number,
                 row,
                 range: (re_match.start(), re_match.end() - 1),
-            }
+            })
         })
         .collect()
 }
@@ -44,7 +74,7 @@ fn cmp_sides(part_number: &PartNumber, lines: &[String], rows: usize, offset: us
     false
 }
 
-fn symbol_around(part_number: &PartNumber, lines: &Vec<String>) -> bool {
+fn symbol_around(part_number: &PartNumber, lines: &[String]) -> bool {
     let (left, right) = part_number.range;
     let columns = lines[0].len();
     let row = part_number.row;
@@ -77,12 +107,22 @@ fn symbol_around(part_number: &PartNumber, lines: &Vec<String>) -> bool {
     false
 }
 
-fn get_part_sum(filename: &str) -> u32 {
-    let lines: Vec<String> = utils::read_lines(filename).collect();
+pub fn get_schematic(input: &str) -> Result<Schematic, ParseError> {
+    let lines = get_lines(input)?;
+    let part_numbers = get_part_numbers(&lines)?;
+
+    Ok(Schematic {
+        lines: lines.into_iter().map(str::to_owned).collect(),
+        part_numbers,
+    })
+}
+
+pub fn get_part_sum(schematic: &Schematic) -> u32 {
+    let lines = &schematic.lines;
 
     let mut part_sum = 0;
-    for part_number in get_part_numbers(&lines) {
-        if symbol_around(&part_number, &lines) {
+    for part_number in schematic.part_numbers.iter() {
+        if symbol_around(part_number, lines) {
             part_sum += part_number.number;
             continue;
         }
@@ -111,7 +151,7 @@ fn get_side_index(
     None
 }
 
-fn get_gear_index(part_number: &PartNumber, lines: &Vec<String>) -> Option<(usize, usize)> {
+fn get_gear_index(part_number: &PartNumber, lines: &[String]) -> Option<(usize, usize)> {
     let (left, right) = part_number.range;
     let columns = lines[0].len();
     let row = part_number.row;
@@ -156,8 +196,8 @@ fn get_gear_index(part_number: &PartNumber, lines: &Vec<String>) -> Option<(usiz
     None
 }
 
-fn get_ratio_sum(filename: &str) -> u32 {
-    let lines: Vec<String> = utils::read_lines(filename).collect();
+pub fn get_ratio_sum(schematic: &Schematic) -> u32 {
+    let lines = &schematic.lines;
 
     let gear_re = Regex::new(r"\*").unwrap();
     let mut gears: HashMap<(usize, usize), Vec<u32>> = HashMap::new();
@@ -172,8 +212,8 @@ fn get_ratio_sum(filename: &str) -> u32 {
         gears.insert((row, re_match.start()), vec![]);
     }
 
-    for part_number in get_part_numbers(&lines) {
-        if let Some((row, column)) = get_gear_index(&part_number, &lines) {
+    for part_number in schematic.part_numbers.iter() {
+        if let Some((row, column)) = get_gear_index(part_number, lines) {
             gears
                 .entry((row, column))
                 .and_modify(|v| v.push(part_number.number));
@@ -189,24 +229,47 @@ fn get_ratio_sum(filename: &str) -> u32 {
     ratio_sum
 }
 
-fn test() {
-    assert_eq!(get_part_sum("src/d3/test_input.dat"), 4361);
-    assert_eq!(get_ratio_sum("src/d3/test_input.dat"), 467835);
-}
+pub struct Day3;
 
-pub fn test_final() {
-    assert_eq!(get_part_sum("src/d3/full_input.dat"), 536576);
-    assert_eq!(get_ratio_sum("src/d3/full_input.dat"), 75741499);
+impl Solver for Day3 {
+    type Input = Schematic;
+
+    const DAY: u32 = 3;
+    const TITLE: &'static str = "Gear Ratios";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        get_schematic(input)
+    }
+
+    fn part_one(&self, schematic: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(get_part_sum(schematic).into())
+    }
+
+    fn part_two(&self, schematic: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(get_ratio_sum(schematic).into())
+    }
 }
 
-pub fn main() {
-    test();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
 
-    let mut now = Instant::now();
-    let sum_p1 = get_part_sum("src/d3/full_input.dat");
-    println!("Part one result: {} (took {:?})", sum_p1, now.elapsed());
+    #[test]
+    fn part_one_example() {
+        let input = utils::read_input("src/d3/test_input.dat");
+        assert_eq!(get_part_sum(&get_schematic(&input).unwrap()), 4361);
+    }
 
-    now = Instant::now();
-    let sum_p2 = get_ratio_sum("src/d3/full_input.dat");
-    println!("Part two result: {} (took {:?})", sum_p2, now.elapsed());
+    #[test]
+    fn part_two_example() {
+        let input = utils::read_input("src/d3/test_input.dat");
+        assert_eq!(get_ratio_sum(&get_schematic(&input).unwrap()), 467835);
+    }
+
+    #[test]
+    fn uneven_rows() {
+        let err = get_schematic("467..\n...*..\n").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
 }
@@ -1,78 +1,135 @@
 use regex::{Captures, Regex};
-use std::{collections::HashSet, time::Instant};
+use std::collections::HashSet;
 
+use crate::error::{ParseError, ParseErrorKind, SolveError};
+use crate::solver::{Answer, Solver};
 use crate::utils;
 
-fn capture_to_set(index: usize, capture: &Captures, line: &str) -> HashSet<u32> {
-    HashSet::from_iter(utils::string_to_iter(
-        capture
-            .get(index)
-            .unwrap_or_else(|| panic!("capture {} does not exist for line {}!", index, line))
-            .as_str(),
-        " ",
-        0,
-    ))
+fn capture_to_set(
+    index: usize,
+    capture: &Captures,
+    line: &str,
+) -> Result<HashSet<u32>, ParseError> {
+    let numbers = capture
+        .get(index)
+        .unwrap_or_else(|| panic!("capture {} does not exist for line {}!", index, line))
+        .as_str();
+
+    utils::string_to_iter(numbers, " ", 0)
+        .map(|number| number.map_err(|err| err.within(line, numbers)))
+        .collect()
 }
 
-fn get_winning_count(line: &str, regex: &Regex) -> u32 {
-    let captures = regex
-        .captures(line)
-        .unwrap_or_else(|| panic!("got no captures for line {}!", line));
+/// Counts the winning numbers on a card. Errors point into 'line'.
+fn get_winning_count(line: &str, regex: &Regex) -> Result<u32, ParseError> {
+    let captures = regex.captures(line).ok_or_else(|| {
+        ParseError::at(
+            line,
+            line,
+            ParseErrorKind::Expected("`Card <id>: <numbers> | <numbers>`"),
+        )
+    })?;
 
-    let winners = capture_to_set(1, &captures, line);
-    let numbers = capture_to_set(2, &captures, line);
+    let winners = capture_to_set(1, &captures, line)?;
+    let numbers = capture_to_set(2, &captures, line)?;
 
-    numbers.intersection(&winners).count() as u32
+    Ok(numbers.intersection(&winners).count() as u32)
 }
 
-fn get_point_sum(filename: &str) -> u32 {
+/// Counts the winning numbers on every card.
+pub fn get_winning_counts(input: &str) -> Result<Vec<u32>, ParseError> {
     let number_regex = Regex::new(r"Card +\d+: +((?:\d+ *)+) \| +((?:\d+ *)+)")
         .expect("number_regex could not be parsed!");
 
-    utils::read_lines(filename)
-        .filter_map(|line| match get_winning_count(&line, &number_regex) {
-            0 => None,
-            c => Some(2_u32.pow(c - 1)),
-        })
-        .sum()
+    input
+        .lines()
+        .map(|line| get_winning_count(line, &number_regex).map_err(|err| err.within(input, line)))
+        .collect()
 }
 
-fn get_card_count(filename: &str) -> u32 {
-    let number_regex = Regex::new(r"Card +\d+: +((?:\d+ *)+) \| +((?:\d+ *)+)")
-        .expect("number_regex could not be parsed!");
+pub fn get_point_sum(winning_counts: &[u32]) -> u32 {
+    let mut point_sum = 0;
+    for &winning_count in winning_counts {
+        match winning_count {
+            0 => {}
+            c => point_sum += 2_u32.pow(c - 1),
+        }
+    }
 
-    let lines: Vec<String> = utils::read_lines(filename).collect();
-    let mut card_numbers = vec![1_u32; lines.len()];
+    point_sum
+}
 
-    for (i, line) in lines.iter().enumerate() {
-        let winning_count = get_winning_count(line, &number_regex);
+pub fn get_card_count(winning_counts: &[u32]) -> Result<u32, SolveError> {
+    let mut card_numbers = vec![1_u32; winning_counts.len()];
+
+    for (i, &winning_count) in winning_counts.iter().enumerate() {
+        if i + winning_count as usize >= winning_counts.len() {
+            return Err(SolveError::Invalid(format!(
+                "card {} wins copies past the last card",
+                i + 1
+            )));
+        }
 
         for j in 1..(winning_count as usize + 1) {
             card_numbers[i + j] += card_numbers[i]
         }
     }
 
-    card_numbers.iter().sum()
+    Ok(card_numbers.iter().sum())
 }
 
-fn test() {
-    assert_eq!(get_point_sum("src/d4/test_input.dat"), 13);
-    assert_eq!(get_card_count("src/d4/test_input.dat"), 30);
-}
+pub struct Day4;
+
+impl Solver for Day4 {
+    type Input = Vec<u32>;
 
-pub fn test_final() {
-    assert_eq!(get_point_sum("src/d4/full_input.dat"), 15205);
-    assert_eq!(get_card_count("src/d4/full_input.dat"), 6189740);
+    const DAY: u32 = 4;
+    const TITLE: &'static str = "Scratchcards";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        get_winning_counts(input)
+    }
+
+    fn part_one(&self, winning_counts: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(get_point_sum(winning_counts).into())
+    }
+
+    fn part_two(&self, winning_counts: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(get_card_count(winning_counts)?.into())
+    }
 }
 
-pub fn main() {
-    test();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_one_example() {
+        let input = utils::read_input("src/d4/test_input.dat");
+        assert_eq!(get_point_sum(&get_winning_counts(&input).unwrap()), 13);
+    }
 
-    let mut now = Instant::now();
-    let sum_p1 = get_point_sum("src/d4/full_input.dat");
-    println!("Part one result: {} (took {:?})", sum_p1, now.elapsed());
+    #[test]
+    fn part_two_example() {
+        let input = utils::read_input("src/d4/test_input.dat");
+        assert_eq!(get_card_count(&get_winning_counts(&input).unwrap()), Ok(30));
+    }
 
-    now = Instant::now();
-    let sum_p2 = get_card_count("src/d4/full_input.dat");
-    println!("Part two result: {} (took {:?})", sum_p2, now.elapsed());
+    #[test]
+    fn missing_separator() {
+        let err = get_winning_counts("Card 1: 41 48 83 86 17 83 86  6 31 17  9 48 53").unwrap_err();
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::Expected("`Card <id>: <numbers> | <numbers>`")
+        );
+    }
+
+    #[test]
+    fn copies_past_the_last_card() {
+        assert_eq!(get_point_sum(&[0, 1]), 1);
+        assert!(matches!(
+            get_card_count(&[0, 1]),
+            Err(SolveError::Invalid(_))
+        ));
+    }
 }
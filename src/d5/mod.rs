@@ -1,63 +1,313 @@
-use indicatif::{ProgressIterator, ProgressStyle};
-use std::time::Instant;
+use std::ops::Range;
 
-use crate::utils;
+use crate::error::{ParseError, ParseErrorKind, SolveError};
+use crate::solver::{answer_from_u64, Answer, Solver};
+use crate::utils::{self, LinearPiece, RangeSet};
 
-struct WackyMapPart {
-    destination_range_start: u64,
-    source_range_start: u64,
-    range_length: u64,
+/// A single line of an almanac map, mapping a range of source values to a
+/// range of destination values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WackyMapPart {
+    pub destination_range_start: u64,
+    pub source_range_start: u64,
+    pub range_length: u64,
 }
 
-struct WackyMap {
-    sub_maps: Vec<WackyMapPart>,
+/// A full almanac map, such as `seed-to-soil`. Keys not covered by any of the
+/// sub maps are mapped to themselves.
+#[derive(Debug, Clone, Default)]
+pub struct WackyMap {
+    /// The category the map maps from, such as `seed`.
+    pub source: String,
+    /// The category the map maps to, such as `soil`.
+    pub destination: String,
+    pub sub_maps: Vec<WackyMapPart>,
 }
 
-struct MapPipe {
-    maps: Vec<WackyMap>,
+/// A list of almanac maps. When used to map values, the maps are applied in
+/// order; use [`MapPipe::chain`] to get the maps between two categories.
+#[derive(Debug, Clone, Default)]
+pub struct MapPipe {
+    pub maps: Vec<WackyMap>,
+}
+
+/// A problem with the sub maps of a [`WackyMap`]. The numbers are indices
+/// into [`WackyMap::sub_maps`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapIssue {
+    /// The sub map has a length of zero, so it maps nothing.
+    ZeroLength(usize),
+    /// The source or destination range of the sub map goes past `u64::MAX`.
+    /// Only the values that fit are mapped.
+    Overflow(usize),
+    /// The source ranges of two sub maps overlap. The first one wins, so the
+    /// second one is partly or fully ignored.
+    Overlap(usize, usize),
+}
+
+impl MapIssue {
+    /// The sub map the issue should be reported at.
+    pub fn sub_map(&self) -> usize {
+        match *self {
+            MapIssue::ZeroLength(i) | MapIssue::Overflow(i) => i,
+            MapIssue::Overlap(first, second) => first.max(second),
+        }
+    }
+
+    /// What a valid sub map would look like, for use in a [`ParseError`].
+    pub fn expected(&self) -> &'static str {
+        match self {
+            MapIssue::ZeroLength(_) => "a range length above zero",
+            MapIssue::Overflow(_) => "ranges whose last value is at most 2^64 - 1",
+            MapIssue::Overlap(..) => "a source range that does not overlap an earlier one",
+        }
+    }
+}
+
+/// The seeds to be planted, and the maps that lead them to their locations.
+#[derive(Debug, Clone)]
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    pub map_pipe: MapPipe,
 }
 
 impl WackyMapPart {
-    fn from_string(string: &str) -> Self {
-        match utils::string_to_array(string, " ", 0)[..] {
-            [a, b, c] => WackyMapPart {
+    /// Parses a `<destination> <source> <length>` line. Errors point into 'string'.
+    pub fn from_string(string: &str) -> Result<Self, ParseError> {
+        match utils::string_to_array(string, " ", 0)?[..] {
+            [a, b, c] => Ok(WackyMapPart {
                 destination_range_start: a,
                 source_range_start: b,
                 range_length: c,
-            },
-            _ => panic!(
-                "WackyMapPart::from_string got malformed string: {:?}",
-                string
-            ),
+            }),
+            _ => Err(ParseError::at(
+                string,
+                string,
+                ParseErrorKind::Expected("`<destination> <source> <length>`"),
+            )),
         }
     }
+
+    /// The length of the ranges, cut short if either range would go past
+    /// `u64::MAX`. The ranges are half-open, so the value `u64::MAX` itself is
+    /// never mapped, even by a sub map that validly ends there.
+    fn usable_length(&self) -> u64 {
+        self.range_length
+            .min(u64::MAX - self.source_range_start)
+            .min(u64::MAX - self.destination_range_start)
+    }
+
+    /// Whether the source or destination range goes past `u64::MAX`.
+    pub fn overflows(&self) -> bool {
+        let ends_past_max = |start: u64| start as u128 + self.range_length as u128 > 1 << 64;
+        ends_past_max(self.source_range_start) || ends_past_max(self.destination_range_start)
+    }
+
+    /// The source values mapped by this part.
+    pub fn source_range(&self) -> Range<u64> {
+        self.source_range_start..(self.source_range_start + self.usable_length())
+    }
+
+    /// The values the source range is mapped to.
+    pub fn destination_range(&self) -> Range<u64> {
+        self.destination_range_start..(self.destination_range_start + self.usable_length())
+    }
+
+    /// The part of this map that covers the source values in `range`, if any.
+    pub fn restrict(&self, range: Range<u64>) -> Option<WackyMapPart> {
+        let source = self.source_range();
+        let start = range.start.max(source.start);
+        let end = range.end.min(source.end);
+
+        (start < end).then(|| WackyMapPart {
+            destination_range_start: start - source.start + self.destination_range_start,
+            source_range_start: start,
+            range_length: end - start,
+        })
+    }
+}
+
+impl LinearPiece<u64> for WackyMapPart {
+    fn source(&self) -> Range<u64> {
+        self.source_range()
+    }
+
+    fn destination_start(&self) -> u64 {
+        self.destination_range_start
+    }
 }
 
 impl WackyMap {
-    fn new() -> Self {
-        WackyMap { sub_maps: vec![] }
+    pub fn new() -> Self {
+        WackyMap::default()
     }
 
-    fn map(&self, key: u64) -> u64 {
-        for part in self.sub_maps.iter() {
-            let start = part.source_range_start;
-            let end = start + part.range_length;
+    /// Creates an empty map from one category to another.
+    pub fn between(source: &str, destination: &str) -> Self {
+        WackyMap {
+            source: source.to_owned(),
+            destination: destination.to_owned(),
+            sub_maps: vec![],
+        }
+    }
 
-            if (start..=end).contains(&key) {
-                return key - start + part.destination_range_start;
+    pub fn map(&self, key: u64) -> u64 {
+        for part in self.sub_maps.iter() {
+            if part.source_range().contains(&key) {
+                return key - part.source_range_start + part.destination_range_start;
             }
         }
 
         key
     }
+
+    /// Maps every value in `ranges`, splitting ranges where they cross the
+    /// edges of the sub maps.
+    pub fn map_ranges(&self, ranges: &RangeSet<u64>) -> RangeSet<u64> {
+        ranges.map_through(&self.sub_maps)
+    }
+
+    /// Finds sub maps that are empty, overflow, or overlap an earlier sub map.
+    pub fn validate(&self) -> Vec<MapIssue> {
+        let mut issues = vec![];
+
+        for (i, part) in self.sub_maps.iter().enumerate() {
+            if part.range_length == 0 {
+                issues.push(MapIssue::ZeroLength(i));
+            } else if part.overflows() {
+                issues.push(MapIssue::Overflow(i));
+            }
+        }
+
+        let mut order: Vec<usize> = (0..self.sub_maps.len())
+            .filter(|&i| self.sub_maps[i].range_length > 0)
+            .collect();
+        order.sort_by_key(|&i| self.sub_maps[i].source_range_start);
+
+        // the sub map reaching furthest so far, which any overlap must hit
+        let mut furthest: Option<usize> = None;
+        for i in order {
+            let range = self.sub_maps[i].source_range();
+            if let Some(j) = furthest {
+                let furthest_end = self.sub_maps[j].source_range().end;
+                if range.start < furthest_end {
+                    issues.push(MapIssue::Overlap(j.min(i), j.max(i)));
+                }
+                if range.end <= furthest_end {
+                    continue;
+                }
+            }
+            furthest = Some(i);
+        }
+
+        issues.sort_by_key(MapIssue::sub_map);
+        issues
+    }
+
+    /// The source values covered by any of the sub maps.
+    pub fn covered(&self) -> RangeSet<u64> {
+        self.sub_maps
+            .iter()
+            .map(WackyMapPart::source_range)
+            .collect()
+    }
+
+    /// The sub maps sorted by source, with the values that an earlier sub map
+    /// already covers cut away, so that no two parts overlap.
+    pub fn disjoint_parts(&self) -> Vec<WackyMapPart> {
+        let mut covered = RangeSet::new();
+        let mut parts = vec![];
+
+        for part in self.sub_maps.iter() {
+            let uncovered = RangeSet::from(part.source_range()).difference(&covered);
+            parts.extend(
+                uncovered
+                    .iter()
+                    .filter_map(|range| part.restrict(range.clone())),
+            );
+            covered.insert(part.source_range());
+        }
+
+        parts.sort_by_key(|part| part.source_range_start);
+        parts
+    }
+
+    /// This map restricted to the source values in `range`, as disjoint parts
+    /// that cover all of `range`. Values not covered by any sub map get a part
+    /// that maps them to themselves.
+    pub fn parts_over(&self, range: Range<u64>) -> Vec<WackyMapPart> {
+        let mut parts: Vec<WackyMapPart> = self
+            .disjoint_parts()
+            .iter()
+            .filter_map(|part| part.restrict(range.clone()))
+            .collect();
+
+        let uncovered = RangeSet::from(range).difference(&self.covered());
+        parts.extend(uncovered.iter().map(|range| WackyMapPart {
+            destination_range_start: range.start,
+            source_range_start: range.start,
+            range_length: range.end - range.start,
+        }));
+
+        parts.sort_by_key(|part| part.source_range_start);
+        parts
+    }
+
+    /// A single map equivalent to applying this map, and then `next`.
+    pub fn compose(&self, next: &WackyMap) -> WackyMap {
+        let mut sub_maps = vec![];
+
+        // values moved by this map, and then maybe by next
+        for part in self.disjoint_parts() {
+            for next_part in next.parts_over(part.destination_range()) {
+                let offset = next_part.source_range_start - part.destination_range_start;
+                sub_maps.push(WackyMapPart {
+                    destination_range_start: next_part.destination_range_start,
+                    source_range_start: part.source_range_start + offset,
+                    range_length: next_part.range_length,
+                });
+            }
+        }
+
+        // values only moved by next
+        let untouched = next.covered().difference(&self.covered());
+        for next_part in next.disjoint_parts() {
+            for range in untouched.iter() {
+                sub_maps.extend(next_part.restrict(range.clone()));
+            }
+        }
+
+        sub_maps.retain(|part| part.source_range_start != part.destination_range_start);
+        sub_maps.sort_by_key(|part| part.source_range_start);
+        WackyMap {
+            sub_maps,
+            ..WackyMap::between(&self.source, &next.destination)
+        }
+    }
+
+    /// The inverse mapping: every key that is mapped to a value in `values`.
+    /// A map does not have to be one-to-one, so this is a set of keys.
+    pub fn preimage(&self, values: &RangeSet<u64>) -> RangeSet<u64> {
+        let mut keys = values.difference(&self.covered());
+
+        for part in self.disjoint_parts() {
+            let hit = values.intersection(&RangeSet::from(part.destination_range()));
+            keys.extend(hit.iter().map(|range| {
+                let start = range.start - part.destination_range_start + part.source_range_start;
+                start..(start + range.end - range.start)
+            }));
+        }
+
+        keys
+    }
 }
 
 impl MapPipe {
-    fn new() -> Self {
+    pub fn new() -> Self {
         MapPipe { maps: vec![] }
     }
 
-    fn map(&self, key: u64) -> u64 {
+    pub fn map(&self, key: u64) -> u64 {
         let mut mapped_value = key;
 
         for map in self.maps.iter() {
@@ -66,94 +316,503 @@ impl MapPipe {
 
         mapped_value
     }
+
+    /// Maps every value in `ranges` through each map in turn.
+    pub fn map_ranges(&self, ranges: &RangeSet<u64>) -> RangeSet<u64> {
+        let mut mapped_ranges = ranges.clone();
+
+        for map in self.maps.iter() {
+            mapped_ranges = map.map_ranges(&mapped_ranges);
+        }
+
+        mapped_ranges
+    }
+
+    /// Collapses the pipe into a single equivalent map.
+    pub fn compose(&self) -> WackyMap {
+        let source = self.maps.first().map_or("", |map| &map.source);
+        self.maps
+            .iter()
+            .fold(WackyMap::between(source, source), |composed, map| {
+                composed.compose(map)
+            })
+    }
+
+    /// Finds the chain of maps that leads from the `from` category to the `to`
+    /// category. Fails if there is no such chain, or more than one.
+    pub fn chain(&self, from: &str, to: &str) -> Result<MapPipe, SolveError> {
+        let mut chains = vec![];
+        self.find_chains(to, &mut vec![from], &mut vec![], &mut chains);
+
+        match &chains[..] {
+            [chain] => Ok(MapPipe {
+                maps: chain.iter().map(|&i| self.maps[i].clone()).collect(),
+            }),
+            [] => Err(SolveError::Invalid(format!(
+                "there is no chain of maps from {:?} to {:?}",
+                from, to
+            ))),
+            _ => Err(SolveError::Invalid(format!(
+                "there is more than one chain of maps from {:?} to {:?}",
+                from, to
+            ))),
+        }
+    }
+
+    /// Walks the category graph depth first, collecting chains of map indices
+    /// that lead to `to`. Stops as soon as two chains are found.
+    fn find_chains<'a>(
+        &'a self,
+        to: &str,
+        visited: &mut Vec<&'a str>,
+        chain: &mut Vec<usize>,
+        chains: &mut Vec<Vec<usize>>,
+    ) {
+        let category = *visited.last().unwrap();
+        if category == to {
+            chains.push(chain.clone());
+            return;
+        }
+
+        for (i, map) in self.maps.iter().enumerate() {
+            if chains.len() > 1
+                || map.source != category
+                || visited.contains(&map.destination.as_str())
+            {
+                continue;
+            }
+
+            visited.push(&map.destination);
+            chain.push(i);
+            self.find_chains(to, visited, chain, chains);
+            chain.pop();
+            visited.pop();
+        }
+    }
+
+    /// Maps a value from the `from` category to the `to` category.
+    pub fn map_between(&self, key: u64, from: &str, to: &str) -> Result<u64, SolveError> {
+        Ok(self.chain(from, to)?.map(key))
+    }
+
+    /// The inverse mapping: every key that ends up at a value in `values`.
+    pub fn preimage(&self, values: &RangeSet<u64>) -> RangeSet<u64> {
+        let mut keys = values.clone();
+
+        for map in self.maps.iter().rev() {
+            keys = map.preimage(&keys);
+        }
+
+        keys
+    }
 }
 
-fn get_map_pipe(lines: &Vec<String>) -> MapPipe {
-    let line_indecies: Vec<usize> = lines
-        .iter()
-        .enumerate()
-        .filter_map(|(i, line)| if line.is_empty() { Some(i + 1) } else { None })
-        .collect();
+/// Parses a `<source>-to-<destination> map:` header. Errors point into 'input'.
+fn get_map_header(input: &str, line: &str) -> Result<WackyMap, ParseError> {
+    line.strip_suffix(" map:")
+        .and_then(|name| name.split_once("-to-"))
+        .filter(|(source, destination)| !source.is_empty() && !destination.is_empty())
+        .map(|(source, destination)| WackyMap::between(source, destination))
+        .ok_or_else(|| {
+            ParseError::at(
+                input,
+                line,
+                ParseErrorKind::Expected("`<source>-to-<destination> map:`"),
+            )
+        })
+}
+
+/// Fails on the first issue found by [`WackyMap::validate`], pointing at the
+/// line of the offending sub map. `lines` holds the line of each sub map.
+fn check_map(input: &str, map: &WackyMap, lines: &[&str]) -> Result<(), ParseError> {
+    match map.validate().first() {
+        Some(issue) => Err(ParseError::at(
+            input,
+            lines[issue.sub_map()],
+            ParseErrorKind::Expected(issue.expected()),
+        )),
+        None => Ok(()),
+    }
+}
 
+/// Parses every map after the seeds. Each map is a header followed by its
+/// sub maps, and maps are separated by blank lines. If `strict` is set, maps
+/// with issues (see [`WackyMap::validate`]) are rejected.
+pub fn get_map_pipe(input: &str, strict: bool) -> Result<MapPipe, ParseError> {
     let mut map_pipe = MapPipe::new();
-    for (map_i, line_i) in line_indecies.iter().enumerate() {
-        let data_lines = if map_i >= line_indecies.len() - 1 {
-            lines.len() - line_i - 1
-        } else {
-            line_indecies[map_i + 1] - line_i - 2
-        };
+    let mut current_map: Option<(WackyMap, Vec<&str>)> = None;
+
+    let mut finish_map = |current_map: Option<(WackyMap, Vec<&str>)>| {
+        if let Some((map, lines)) = current_map {
+            if strict {
+                check_map(input, &map, &lines)?;
+            }
+            map_pipe.maps.push(map);
+        }
+        Ok(())
+    };
+
+    for line in input.lines().skip_while(|line| !line.is_empty()) {
+        if line.is_empty() {
+            finish_map(current_map.take())?;
+            continue;
+        }
 
-        map_pipe.maps.push(WackyMap::new());
-        for data_line in 1..(data_lines + 1) {
-            map_pipe.maps[map_i]
-                .sub_maps
-                .push(WackyMapPart::from_string(&lines[line_i + data_line]));
+        match current_map.as_mut() {
+            None => current_map = Some((get_map_header(input, line)?, vec![])),
+            Some((map, lines)) => {
+                map.sub_maps
+                    .push(WackyMapPart::from_string(line).map_err(|err| err.within(input, line))?);
+                lines.push(line);
+            }
         }
     }
+    finish_map(current_map)?;
 
-    map_pipe
+    Ok(map_pipe)
 }
 
-fn get_min_location(filename: &str) -> u64 {
-    let lines: Vec<String> = utils::read_lines(filename).collect();
-
-    let map_pipe = get_map_pipe(&lines);
+/// Parses an almanac. Sub maps that overlap are allowed, with the first one
+/// taking precedence, see [`get_almanac_strict`] to reject them instead.
+pub fn get_almanac(input: &str) -> Result<Almanac, ParseError> {
+    parse_almanac(input, false)
+}
 
-    utils::string_to_iter(
-        lines[0]
-            .get(7..)
-            .unwrap_or_else(|| panic!("Could not get 7.. from line 0: {}", lines[0])),
-        " ",
-        0,
-    )
-    .map(|seed| map_pipe.map(seed))
-    .min()
-    .expect("location list is empty!?")
+/// Parses an almanac, rejecting maps with empty, overflowing or overlapping
+/// sub maps.
+pub fn get_almanac_strict(input: &str) -> Result<Almanac, ParseError> {
+    parse_almanac(input, true)
 }
 
-fn get_true_min_location(filename: &str) -> u64 {
-    let lines: Vec<String> = utils::read_lines(filename).collect();
+fn parse_almanac(input: &str, strict: bool) -> Result<Almanac, ParseError> {
+    let seed_line = input.lines().next().unwrap_or_default();
+    let seed_string = seed_line.strip_prefix("seeds: ").ok_or_else(|| {
+        ParseError::at(
+            input,
+            seed_line,
+            ParseErrorKind::Expected("`seeds: <numbers>`"),
+        )
+    })?;
 
-    let map_pipe = get_map_pipe(&lines);
+    let seeds = utils::string_to_array(seed_string, " ", 0)
+        .map_err(|err| err.within(input, seed_string))?;
 
-    utils::string_to_array(
-        lines[0]
-            .get(7..)
-            .unwrap_or_else(|| panic!("Could not get 7.. from line 0: {}", lines[0])),
-        " ",
-        0,
-    )
-    .chunks(2)
-    .progress_with_style(
-        ProgressStyle::with_template("{wide_bar} {pos}/{len} [{elapsed} : {eta}] ").unwrap(),
-    )
-    .flat_map(|v| match v {
-        [start, range] => (*start)..(start + range),
-        _ => panic!("what? {:?}", v),
+    Ok(Almanac {
+        seeds,
+        map_pipe: get_map_pipe(input, strict)?,
     })
-    .map(|seed| map_pipe.map(seed))
-    .min()
-    .expect("location list is empty!?")
 }
 
-fn test() {
-    assert_eq!(get_min_location("src/d5/test_input.dat"), 35);
-    assert_eq!(get_true_min_location("src/d5/test_input.dat"), 46);
+fn no_seeds_error() -> SolveError {
+    SolveError::Invalid("the almanac has no seeds".to_owned())
+}
+
+impl Almanac {
+    /// The chain of maps from seeds to locations.
+    pub fn location_pipe(&self) -> Result<MapPipe, SolveError> {
+        self.map_pipe.chain("seed", "location")
+    }
+}
+
+pub fn get_min_location(almanac: &Almanac) -> Result<u64, SolveError> {
+    let location_pipe = almanac.location_pipe()?;
+    almanac
+        .seeds
+        .iter()
+        .map(|seed| location_pipe.map(*seed))
+        .min()
+        .ok_or_else(no_seeds_error)
+}
+
+/// Reads the seeds as pairs of range start and length.
+fn get_seed_ranges(almanac: &Almanac) -> Result<RangeSet<u64>, SolveError> {
+    if !almanac.seeds.len().is_multiple_of(2) {
+        return Err(SolveError::Invalid(
+            "the seeds do not come in pairs of start and length".to_owned(),
+        ));
+    }
+
+    Ok(almanac
+        .seeds
+        .chunks(2)
+        .map(|v| v[0]..v[0].saturating_add(v[1]))
+        .collect())
+}
+
+pub fn get_true_min_location(almanac: &Almanac) -> Result<u64, SolveError> {
+    almanac
+        .location_pipe()?
+        .map_ranges(&get_seed_ranges(almanac)?)
+        .min()
+        .ok_or_else(no_seeds_error)
+}
+
+/// Finds the same location as [`get_true_min_location`], but by searching
+/// backwards: the parts of the composed pipe are visited from the lowest
+/// location up, until no part can reach a location below the best one found.
+pub fn get_true_min_location_reverse(almanac: &Almanac) -> Result<u64, SolveError> {
+    let seed_ranges = get_seed_ranges(almanac)?;
+    let composed = almanac.location_pipe()?.compose();
+
+    let mut parts = composed.parts_over(0..u64::MAX);
+    parts.sort_by_key(|part| part.destination_range_start);
+
+    let mut best: Option<u64> = None;
+    for part in parts {
+        if best.is_some_and(|best| best <= part.destination_range_start) {
+            break;
+        }
+
+        let locations = RangeSet::from(part.destination_range());
+        let seeds = composed.preimage(&locations).intersection(&seed_ranges);
+        if let Some(location) = composed.map_ranges(&seeds).min() {
+            best = Some(best.map_or(location, |best| best.min(location)));
+        }
+    }
+
+    best.ok_or_else(no_seeds_error)
 }
 
-pub fn test_final() {
-    assert_eq!(get_min_location("src/d5/full_input.dat"), 806029445);
-    // assert_eq!(get_true_min_location("src/d5/full_input.dat"), 59370573);
+pub struct Day5;
+
+impl Solver for Day5 {
+    type Input = Almanac;
+
+    const DAY: u32 = 5;
+    const TITLE: &'static str = "If You Give A Seed A Fertilizer";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        // a hand-edited almanac with overlapping maps would give a wrong answer
+        get_almanac_strict(input)
+    }
+
+    fn part_one(&self, almanac: &Self::Input) -> Result<Answer, SolveError> {
+        answer_from_u64(get_min_location(almanac)?)
+    }
+
+    fn part_two(&self, almanac: &Self::Input) -> Result<Answer, SolveError> {
+        answer_from_u64(get_true_min_location(almanac)?)
+    }
 }
 
-pub fn main() {
-    test();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_one_example() {
+        let almanac = get_almanac(&utils::read_input("src/d5/test_input.dat")).unwrap();
+        assert_eq!(get_min_location(&almanac), Ok(35));
+    }
 
-    let now = Instant::now();
-    let sum_p1 = get_min_location("src/d5/full_input.dat");
-    println!("Part one result: {} (took {:?})", sum_p1, now.elapsed());
+    #[test]
+    fn part_two_example() {
+        let almanac = get_almanac(&utils::read_input("src/d5/test_input.dat")).unwrap();
+        assert_eq!(get_true_min_location(&almanac), Ok(46));
+    }
 
-    // now = Instant::now();
-    // let sum_p2 = get_true_min_location("src/d5/full_input.dat");
-    // println!("Part two result: {} (took {:?})", sum_p2, now.elapsed());
+    #[test]
+    fn wacky_map_part() {
+        let part = WackyMapPart::from_string("50 98 2").unwrap();
+        assert_eq!(
+            part,
+            WackyMapPart {
+                destination_range_start: 50,
+                source_range_start: 98,
+                range_length: 2,
+            }
+        );
+        assert!(WackyMapPart::from_string("50 98").is_err());
+    }
+
+    #[test]
+    fn map_ranges_splits_at_edges() {
+        let map = WackyMap {
+            sub_maps: vec![
+                WackyMapPart::from_string("50 98 2").unwrap(),
+                WackyMapPart::from_string("52 50 48").unwrap(),
+            ],
+            ..WackyMap::between("seed", "soil")
+        };
+
+        let seeds: RangeSet<u64> = [40..50, 95..105].into_iter().collect();
+        let soils: RangeSet<u64> = [40..52, 97..105].into_iter().collect();
+        assert_eq!(map.map_ranges(&seeds), soils);
+    }
+
+    #[test]
+    fn compose_agrees_with_pipe() {
+        let almanac = get_almanac(&utils::read_input("src/d5/test_input.dat")).unwrap();
+        let composed = almanac.map_pipe.compose();
+        for seed in 0..120 {
+            assert_eq!(composed.map(seed), almanac.map_pipe.map(seed));
+        }
+    }
+
+    #[test]
+    fn compose_keeps_first_sub_map() {
+        let first = WackyMap {
+            sub_maps: vec![
+                WackyMapPart::from_string("100 0 10").unwrap(),
+                WackyMapPart::from_string("200 5 10").unwrap(),
+            ],
+            ..WackyMap::between("a", "b")
+        };
+        let second = WackyMap {
+            sub_maps: vec![WackyMapPart::from_string("0 100 5").unwrap()],
+            ..WackyMap::between("b", "c")
+        };
+
+        let composed = first.compose(&second);
+        assert_eq!((&*composed.source, &*composed.destination), ("a", "c"));
+        for key in 0..220 {
+            assert_eq!(composed.map(key), second.map(first.map(key)));
+        }
+    }
+
+    #[test]
+    fn preimage_agrees_with_map() {
+        let almanac = get_almanac(&utils::read_input("src/d5/test_input.dat")).unwrap();
+        let locations: RangeSet<u64> = [30..50, 80..85].into_iter().collect();
+        let seeds = almanac.map_pipe.preimage(&locations);
+
+        for seed in 0..120 {
+            let location = almanac.map_pipe.map(seed);
+            assert_eq!(seeds.contains(seed), locations.contains(location));
+        }
+    }
+
+    #[test]
+    fn reverse_search_example() {
+        let almanac = get_almanac(&utils::read_input("src/d5/test_input.dat")).unwrap();
+        assert_eq!(get_true_min_location_reverse(&almanac), Ok(46));
+    }
+
+    #[test]
+    fn map_ranges_agrees_with_map() {
+        let almanac = get_almanac(&utils::read_input("src/d5/test_input.dat")).unwrap();
+        for seed in 0..120 {
+            let location = almanac.map_pipe.map(seed);
+            let locations = almanac.map_pipe.map_ranges(&RangeSet::from(seed..seed + 1));
+            assert_eq!(locations, RangeSet::from(location..location + 1));
+        }
+    }
+
+    #[test]
+    fn map_between_categories() {
+        let almanac = get_almanac(&utils::read_input("src/d5/test_input.dat")).unwrap();
+        let headers: Vec<_> = almanac
+            .map_pipe
+            .maps
+            .iter()
+            .map(|map| format!("{}-to-{}", map.source, map.destination))
+            .collect();
+        assert_eq!(headers[0], "seed-to-soil");
+        assert_eq!(headers[6], "humidity-to-location");
+
+        // seed 79 has soil 81 and humidity 78
+        let map_pipe = &almanac.map_pipe;
+        assert_eq!(map_pipe.map_between(81, "soil", "humidity"), Ok(78));
+        assert_eq!(map_pipe.map_between(79, "seed", "seed"), Ok(79));
+        assert!(map_pipe.map_between(81, "soil", "seed").is_err());
+    }
+
+    #[test]
+    fn maps_in_any_order() {
+        let input = "seeds: 1 2\n\nb-to-location map:\n10 1 5\n\nseed-to-b map:\n1 2 1";
+        let almanac = get_almanac(input).unwrap();
+        assert_eq!(get_min_location(&almanac), Ok(10));
+    }
+
+    #[test]
+    fn ambiguous_chain() {
+        let input = "seeds: 1\n\nseed-to-a map:\n\nseed-to-b map:\n\n\
+            a-to-location map:\n\nb-to-location map:\n";
+        let almanac = get_almanac(input).unwrap();
+        assert!(matches!(
+            almanac.location_pipe(),
+            Err(SolveError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn bad_header() {
+        let input = "seeds: 1\n\nseed-to-soil map:\n1 2 3\n\nsoil to water:\n";
+        let err = get_almanac(input).unwrap_err();
+        assert_eq!((err.line, err.column), (6, 1));
+    }
+
+    #[test]
+    fn validate_finds_issues() {
+        let map = WackyMap {
+            sub_maps: [
+                "0 10 10",
+                "50 15 2",
+                "5 5 0",
+                "0 18446744073709551610 10",
+                "90 0 10",
+            ]
+            .into_iter()
+            .map(|line| WackyMapPart::from_string(line).unwrap())
+            .collect(),
+            ..WackyMap::between("a", "b")
+        };
+
+        assert_eq!(
+            map.validate(),
+            [
+                MapIssue::Overlap(0, 1),
+                MapIssue::ZeroLength(2),
+                MapIssue::Overflow(3)
+            ]
+        );
+        assert_eq!(map.map(18446744073709551614), 4);
+        assert_eq!(map.map(18446744073709551615), 18446744073709551615);
+    }
+
+    #[test]
+    fn ranges_may_end_at_max() {
+        let part = WackyMapPart::from_string("0 18446744073709551615 1").unwrap();
+        assert!(!part.overflows());
+        assert!(WackyMapPart::from_string("0 18446744073709551615 2")
+            .unwrap()
+            .overflows());
+
+        let input = "seeds: 1\n\nseed-to-location map:\n0 18446744073709551615 1\n";
+        assert!(get_almanac_strict(input).is_ok());
+    }
+
+    #[test]
+    fn answers_too_large_for_i64() {
+        let almanac = Day5
+            .parse("seeds: 10000000000000000000 1\n\nseed-to-location map:\n0 0 1\n")
+            .unwrap();
+        assert_eq!(Day5.part_one(&almanac), Ok(1));
+        assert!(matches!(
+            Day5.part_two(&almanac),
+            Err(SolveError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn strict_rejects_overlap() {
+        let input = "seeds: 1\n\nseed-to-location map:\n0 10 10\n50 15 2\n";
+        assert_eq!(get_almanac(input).unwrap().map_pipe.map(16), 6);
+
+        let err = get_almanac_strict(input).unwrap_err();
+        assert_eq!((err.line, err.column), (5, 1));
+        assert_eq!(err.text, "50 15 2");
+    }
+
+    #[test]
+    fn boundary_keys_are_not_mapped() {
+        let part = WackyMapPart::from_string("50 98 2").unwrap();
+        let map = WackyMap {
+            sub_maps: vec![part],
+            ..WackyMap::between("a", "b")
+        };
+        assert_eq!(map.map(97), 97);
+        assert_eq!(map.map(99), 51);
+        assert_eq!(map.map(100), 100);
+    }
 }
@@ -1,67 +1,444 @@
-use std::time::Instant;
+use std::ops::RangeInclusive;
 
+use crate::error::{ParseError, ParseErrorKind, SolveError};
+use crate::solver::{answer_from_u64, Answer, Solver};
 use crate::utils;
 
-fn get_win_count(time: u64, record: u64) -> u64 {
-    // simple math gives this as the range of values that beat the record.
-    // The "- 1e-10" is there in case win_range is an integer. In that case,
-    // we would get times where the distance traveled is equal to the record,
-    // but we want ones that are strictly larger.
-    let win_range = ((time.pow(2) - 4 * record) as f64).sqrt() - 1e-10;
+/// Whether holding the button for `hold` ms beats the record.
+fn beats_record(time: u64, record: u64, hold: u64) -> bool {
+    hold as u128 * (time - hold) as u128 > record as u128
+}
+
+/// Counts the ways to beat the record, in exact integer arithmetic.
+pub fn get_win_count(time: u64, record: u64) -> u64 {
+    match get_min_winning_hold(time, record) {
+        // the winning holds are symmetric around time / 2
+        Some(min_hold) => time - 2 * min_hold + 1,
+        None => 0,
+    }
+}
+
+/// Finds the shortest hold that beats the record, if any.
+fn get_min_winning_hold(time: u64, record: u64) -> Option<u64> {
+    // the distance is hold * (time - hold), so the holds that win lie strictly
+    // between the roots of hold^2 - time * hold + record = 0, which are
+    // (time +- sqrt(time^2 - 4 * record)) / 2. The distance is the largest at
+    // time / 2, so if that does not win, nothing does.
+    if !beats_record(time, record, time / 2) {
+        return None;
+    }
+
+    // time^2 < 2^128, and the discriminant is positive as some hold wins
+    let discriminant = (time as u128).pow(2) - 4 * record as u128;
+    let root = discriminant.isqrt() as u64;
+
+    // the integer root can be off by one, so step to the first winning hold
+    let mut min_hold = (time - root) / 2;
+    while min_hold > 0 && beats_record(time, record, min_hold - 1) {
+        min_hold -= 1;
+    }
+    while !beats_record(time, record, min_hold) {
+        min_hold += 1;
+    }
+
+    Some(min_hold)
+}
+
+/// How a boat turns holding the button into speed. The toy boats of the
+/// puzzle are the [`Default`]: one mm/ms of speed per ms held, with no limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Boat {
+    /// The speed gained for each ms the button is held.
+    pub charge_rate: u64,
+    /// The speed can not be charged past this.
+    pub max_speed: Option<u64>,
+    /// Holds shorter than this do not charge the boat at all.
+    pub min_hold: u64,
+}
+
+impl Default for Boat {
+    fn default() -> Self {
+        Boat {
+            charge_rate: 1,
+            max_speed: None,
+            min_hold: 0,
+        }
+    }
+}
+
+/// Finds the first value in `low..=high` where `predicate` holds, given that
+/// it is false up to some value and true after. Returns `None` if it never holds.
+fn binary_search(low: u64, high: u64, predicate: impl Fn(u64) -> bool) -> Option<u64> {
+    let (mut low, mut high) = (low, high);
+    if low > high || !predicate(high) {
+        return None;
+    }
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if predicate(middle) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    Some(low)
+}
+
+impl Boat {
+    /// The speed after holding the button for `hold` ms.
+    pub fn speed(&self, hold: u64) -> u128 {
+        if hold < self.min_hold {
+            return 0;
+        }
+
+        let speed = self.charge_rate as u128 * hold as u128;
+        self.max_speed
+            .map_or(speed, |max_speed| speed.min(max_speed as u128))
+    }
+
+    /// The distance travelled in a race lasting `time` ms, after holding the
+    /// button for `hold` ms. Saturates at `u128::MAX`, which beats any record.
+    pub fn distance(&self, time: u64, hold: u64) -> u128 {
+        let travel_time = time.saturating_sub(hold) as u128;
+        self.speed(hold).saturating_mul(travel_time)
+    }
+
+    /// The hold that gives the longest distance. If several do, any of them.
+    fn best_hold(&self, time: u64) -> u64 {
+        // without a speed limit, the distance is charge_rate * hold * (time - hold),
+        // which peaks at time / 2. With a limit, it only falls after the hold
+        // that reaches the max speed, so the peak is the earlier of the two.
+        let mut best_hold = time / 2;
+        if let (Some(max_speed), true) = (self.max_speed, self.charge_rate > 0) {
+            let unlimited = Boat {
+                min_hold: 0,
+                ..*self
+            };
+            let capped_hold = max_speed.div_ceil(self.charge_rate);
+            best_hold = [best_hold, capped_hold.saturating_sub(1), capped_hold]
+                .into_iter()
+                .filter(|&hold| hold <= time)
+                .max_by_key(|&hold| (unlimited.distance(time, hold), u64::MAX - hold))
+                .unwrap_or(best_hold);
+        }
+
+        // the distance only falls after the peak, so if the peak is too short
+        // a hold, the shortest allowed hold is the best
+        best_hold.max(self.min_hold)
+    }
+
+    /// The holds that beat the record, which always form a single range.
+    pub fn winning_holds(&self, time: u64, record: u64) -> Option<RangeInclusive<u64>> {
+        if self.charge_rate == 0 {
+            return None;
+        }
+
+        if self.max_speed.is_none() {
+            // closed form: the distance beats the record exactly when
+            // hold * (time - hold) beats record / charge_rate, rounded down
+            let min_hold = get_min_winning_hold(time, record / self.charge_rate)?;
+            let holds = min_hold.max(self.min_hold)..=(time - min_hold);
+            return (!holds.is_empty()).then_some(holds);
+        }
+
+        // the distance rises to the best hold and falls after it, so the ends
+        // of the winning range can be found by binary search on either side
+        let beats = |hold| self.distance(time, hold) > record as u128;
+        let best_hold = self.best_hold(time);
+        if best_hold > time || !beats(best_hold) {
+            return None;
+        }
+
+        let first = binary_search(self.min_hold, best_hold, beats)?;
+        let last = binary_search(best_hold, time, |hold| !beats(hold)).map_or(time, |h| h - 1);
+        Some(first..=last)
+    }
+
+    /// Counts the ways this boat can beat the record.
+    pub fn win_count(&self, time: u64, record: u64) -> u64 {
+        self.winning_holds(time, record)
+            .map_or(0, |holds| holds.end() - holds.start() + 1)
+    }
+}
+
+/// Counts the ways to beat the record when any of the boats can be picked.
+pub fn get_fleet_win_count(boats: &[Boat], time: u64, record: u64) -> u64 {
+    boats.iter().map(|boat| boat.win_count(time, record)).sum()
+}
+
+/// Gets the `Time:` and `Distance:` lines of the input.
+fn get_lines(input: &str) -> Result<(&str, &str), ParseError> {
+    let mut lines = input.lines();
 
-    // we must floor and ceil as we are only operating on ints
-    let min_t = ((time as f64 - win_range) / 2.0).ceil() as u64;
-    let max_t = ((time as f64 + win_range) / 2.0).floor() as u64;
+    let mut next_line = |prefix: &str, expected: &'static str| {
+        let line = lines.next().unwrap_or(&input[input.len()..]);
+        if line.starts_with(prefix) {
+            Ok(line)
+        } else {
+            Err(ParseError::at(
+                input,
+                line,
+                ParseErrorKind::Expected(expected),
+            ))
+        }
+    };
 
-    // we add 1 because min_t = max_t gives one way to win
-    max_t - min_t + 1
+    let time_line = next_line("Time:", "`Time: <times>`")?;
+    let record_line = next_line("Distance:", "`Distance: <records>`")?;
+    Ok((time_line, record_line))
 }
 
-fn get_win_prod(filename: &str) -> u64 {
-    let lines: Vec<String> = utils::read_lines(filename).collect();
+fn get_numbers(input: &str, line: &str) -> Result<Vec<u64>, ParseError> {
+    utils::string_to_array(line, " ", 1).map_err(|err| err.within(input, line))
+}
+
+/// A race, and the record distance to beat in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Race {
+    pub time: u64,
+    pub record: u64,
+}
 
-    utils::string_to_iter(&lines[0], " ", 1)
-        .zip(utils::string_to_iter(&lines[1], " ", 1))
-        .map(|(time, record)| get_win_count(time, record))
-        .product()
+/// The races on the sheet, read both as separate races and, with the spaces
+/// between the numbers ignored, as a single long race.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Races {
+    pub races: Vec<Race>,
+    /// The long race, or the error if its numbers do not fit in a `u64`. Only
+    /// part two needs it, so part one can still be solved.
+    pub long_race: Result<Race, ParseError>,
 }
 
-fn unkernel(line: &str) -> u64 {
-    let number_str = utils::string_to_iter(line, " ", 1)
-        .collect::<Vec<String>>()
+fn unkernel(input: &str, line: &str) -> Result<u64, ParseError> {
+    let number_str = line
+        .split(' ')
+        .filter(|c| !c.is_empty())
+        .skip(1)
+        .collect::<Vec<&str>>()
         .join("");
     number_str
         .parse()
-        .unwrap_or_else(|_| panic!("Could not parse string: {:?}", number_str))
+        .map_err(|_| ParseError::at(input, line, ParseErrorKind::InvalidNumber))
 }
 
-fn get_long_win_count(filename: &str) -> u64 {
-    let lines: Vec<String> = utils::read_lines(filename).collect();
+pub fn get_races(input: &str) -> Result<Races, ParseError> {
+    let (time_line, record_line) = get_lines(input)?;
 
-    let true_time = unkernel(&lines[0]);
-    let true_record = unkernel(&lines[1]);
+    let times = get_numbers(input, time_line)?;
+    let records = get_numbers(input, record_line)?;
+    if times.len() != records.len() {
+        return Err(ParseError::at(
+            input,
+            record_line,
+            ParseErrorKind::Expected("as many records as times"),
+        ));
+    }
 
-    get_win_count(true_time, true_record)
+    Ok(Races {
+        races: times
+            .into_iter()
+            .zip(records)
+            .map(|(time, record)| Race { time, record })
+            .collect(),
+        long_race: unkernel(input, time_line).and_then(|time| {
+            Ok(Race {
+                time,
+                record: unkernel(input, record_line)?,
+            })
+        }),
+    })
 }
 
-fn test() {
-    assert_eq!(get_win_prod("src/d6/test_input.dat"), 288);
-    assert_eq!(get_long_win_count("src/d6/test_input.dat"), 71503);
+/// Multiplies the ways to win every race, or errors if the product does not
+/// fit in a `u64`.
+pub fn get_win_prod(races: &[Race]) -> Result<u64, SolveError> {
+    races.iter().try_fold(1_u64, |product, race| {
+        product
+            .checked_mul(get_win_count(race.time, race.record))
+            .ok_or_else(|| {
+                SolveError::Invalid("the product of the win counts overflows".to_owned())
+            })
+    })
 }
 
-pub fn test_final() {
-    assert_eq!(get_win_prod("src/d6/full_input.dat"), 114400);
-    assert_eq!(get_long_win_count("src/d6/full_input.dat"), 21039729);
+pub fn get_long_win_count(races: &Races) -> Result<u64, ParseError> {
+    let long_race = races.long_race.clone()?;
+    Ok(get_win_count(long_race.time, long_race.record))
 }
 
-pub fn main() {
-    test();
+pub struct Day6;
+
+impl Solver for Day6 {
+    type Input = Races;
+
+    const DAY: u32 = 6;
+    const TITLE: &'static str = "Wait For It";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        get_races(input)
+    }
+
+    fn part_one(&self, races: &Self::Input) -> Result<Answer, SolveError> {
+        answer_from_u64(get_win_prod(&races.races)?)
+    }
+
+    fn part_two(&self, races: &Self::Input) -> Result<Answer, SolveError> {
+        answer_from_u64(get_long_win_count(races)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn part_one_example() {
+        let input = utils::read_input("src/d6/test_input.dat");
+        assert_eq!(get_win_prod(&get_races(&input).unwrap().races), Ok(288));
+    }
+
+    #[test]
+    fn part_two_example() {
+        let input = utils::read_input("src/d6/test_input.dat");
+        assert_eq!(get_long_win_count(&get_races(&input).unwrap()), Ok(71503));
+    }
+
+    #[test]
+    fn win_count() {
+        assert_eq!(get_win_count(7, 9), 4);
+        assert_eq!(get_win_count(30, 200), 9);
+    }
+
+    #[test]
+    fn unbeatable_record() {
+        assert_eq!(get_win_count(7, 12), 0);
+        assert_eq!(get_win_count(0, 0), 0);
+        assert_eq!(get_win_count(u64::MAX, u64::MAX), u64::MAX - 3);
+    }
+
+    #[test]
+    fn extreme_races() {
+        // the largest distance is (2^32 - 1) * 2^32 for an odd time of 2^33 - 1
+        let time = (1 << 33) - 1;
+        let max_distance = ((1 << 32) - 1) * (1 << 32);
+        assert_eq!(get_win_count(time, max_distance - 1), 2);
+        assert_eq!(get_win_count(time, max_distance), 0);
+        assert_eq!(get_win_count(u64::MAX, 0), u64::MAX - 1);
+    }
+
+    #[test]
+    fn win_product_overflow() {
+        let races = get_races("Time: 4000000000 4000000000 4000000000\nDistance: 0 0 0").unwrap();
+        assert!(matches!(
+            get_win_prod(&races.races),
+            Err(SolveError::Invalid(_))
+        ));
+        assert_eq!(get_win_prod(&races.races[..2]), Ok(3999999999 * 3999999999));
+    }
+
+    #[test]
+    fn answers_too_large_for_i64() {
+        let races = Day6
+            .parse("Time: 18446744073709551615\nDistance: 0")
+            .unwrap();
+        assert!(matches!(Day6.part_one(&races), Err(SolveError::Invalid(_))));
+        assert!(matches!(Day6.part_two(&races), Err(SolveError::Invalid(_))));
+    }
+
+    fn brute_force_win_count(time: u64, record: u64) -> u64 {
+        (0..=time)
+            .filter(|&hold| hold as u128 * (time - hold) as u128 > record as u128)
+            .count() as u64
+    }
+
+    proptest! {
+        #[test]
+        fn matches_brute_force(time in 0..2000u64, record in 0..1_100_000u64) {
+            prop_assert_eq!(get_win_count(time, record), brute_force_win_count(time, record));
+        }
+
+        #[test]
+        fn matches_brute_force_near_max(time in 0..2000u64, below in 0..1000u64) {
+            let record = (time / 2 * (time - time / 2)).saturating_sub(below);
+            prop_assert_eq!(get_win_count(time, record), brute_force_win_count(time, record));
+        }
+
+        #[test]
+        fn large_races_are_symmetric(time in 0..u64::MAX, record: u64) {
+            let count = get_win_count(time, record);
+            if count > 0 {
+                let min_hold = (time - count).div_ceil(2);
+                prop_assert!(beats_record(time, record, min_hold));
+                prop_assert!(!beats_record(time, record, min_hold - 1));
+                prop_assert!(beats_record(time, record, time - min_hold));
+            }
+        }
+    }
+
+    #[test]
+    fn default_boat_is_toy_boat() {
+        let boat = Boat::default();
+        assert_eq!(boat.win_count(7, 9), 4);
+        assert_eq!(boat.win_count(30, 200), 9);
+        assert_eq!(boat.winning_holds(7, 9), Some(2..=5));
+    }
+
+    #[test]
+    fn boat_variants() {
+        let fast = Boat {
+            charge_rate: 3,
+            ..Boat::default()
+        };
+        // 3 * hold * (7 - hold) > 9 for holds 1 to 6
+        assert_eq!(fast.winning_holds(7, 9), Some(1..=6));
+
+        let capped = Boat {
+            max_speed: Some(2),
+            ..Boat::default()
+        };
+        // distances are 0, 6, 10, 8, 6, 4, 2, 0
+        assert_eq!(capped.winning_holds(7, 5), Some(1..=4));
+
+        let slow_start = Boat {
+            min_hold: 3,
+            ..Boat::default()
+        };
+        assert_eq!(slow_start.winning_holds(7, 9), Some(3..=5));
+        assert_eq!(slow_start.winning_holds(7, 11), Some(3..=4));
+
+        let broken = Boat {
+            charge_rate: 0,
+            ..Boat::default()
+        };
+        assert_eq!(broken.win_count(7, 0), 0);
+
+        assert_eq!(get_fleet_win_count(&[fast, capped, broken], 7, 5), 6 + 4);
+    }
+
+    fn brute_force_boat_win_count(boat: &Boat, time: u64, record: u64) -> u64 {
+        (0..=time)
+            .filter(|&hold| boat.distance(time, hold) > record as u128)
+            .count() as u64
+    }
 
-    let mut now = Instant::now();
-    let sum_p1 = get_win_prod("src/d6/full_input.dat");
-    println!("Part one result: {} (took {:?})", sum_p1, now.elapsed());
+    fn boats() -> impl Strategy<Value = Boat> {
+        (0..6u64, proptest::option::of(0..40u64), 0..30u64).prop_map(
+            |(charge_rate, max_speed, min_hold)| Boat {
+                charge_rate,
+                max_speed,
+                min_hold,
+            },
+        )
+    }
 
-    now = Instant::now();
-    let sum_p2 = get_long_win_count("src/d6/full_input.dat");
-    println!("Part two result: {} (took {:?})", sum_p2, now.elapsed());
+    proptest! {
+        #[test]
+        fn boat_matches_brute_force(boat in boats(), time in 0..200u64, record in 0..3000u64) {
+            let count = boat.win_count(time, record);
+            prop_assert_eq!(count, brute_force_boat_win_count(&boat, time, record));
+        }
+    }
 }
@@ -0,0 +1,381 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::iter;
+
+use super::{Hand, HandType, Ruleset};
+use crate::error::{ParseError, ParseErrorKind};
+
+/// The character marking a card that has not been dealt yet.
+pub const UNKNOWN_CARD: char = '?';
+
+/// The most completions [`Method::Exhaustive`] will try.
+pub const MAX_EXHAUSTIVE_COMPLETIONS: u64 = 10_000_000;
+
+/// A hand where some of the cards are not known yet, such as `KK?7?`. Cards
+/// missing from the end of a short hand are unknown as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialHand {
+    cards: Vec<Option<char>>,
+}
+
+impl PartialHand {
+    /// Parses a partial hand dealt under `ruleset`. Errors point into `string`.
+    pub fn parse(string: &str, ruleset: &Ruleset) -> Result<Self, ParseError> {
+        if string.chars().count() > ruleset.hand_size {
+            return Err(ParseError::at(
+                string,
+                string,
+                ParseErrorKind::Expected("at most as many cards as the hand size of the ruleset"),
+            ));
+        }
+
+        let mut cards = Vec::with_capacity(ruleset.hand_size);
+        for (i, c) in string.char_indices() {
+            if c == UNKNOWN_CARD {
+                cards.push(None);
+            } else if ruleset.card_value(c).is_some() {
+                cards.push(Some(c));
+            } else {
+                return Err(ParseError::at(
+                    string,
+                    &string[i..(i + c.len_utf8())],
+                    ParseErrorKind::Unknown("card"),
+                ));
+            }
+        }
+        cards.resize(ruleset.hand_size, None);
+
+        Ok(PartialHand { cards })
+    }
+
+    /// How many cards are still unknown.
+    pub fn unknown_count(&self) -> usize {
+        self.cards.iter().filter(|card| card.is_none()).count()
+    }
+
+    /// How many ways there are to complete the hand, or `None` if there are
+    /// more than fit in a `u64`.
+    pub fn completion_count(&self, ruleset: &Ruleset) -> Option<u64> {
+        let card_count = ruleset.card_order.chars().count() as u64;
+        card_count.checked_pow(self.unknown_count().try_into().ok()?)
+    }
+
+    /// Fills in the unknown cards, in order, with the cards of `fill`.
+    fn complete(&self, mut fill: impl Iterator<Item = char>) -> String {
+        self.cards
+            .iter()
+            .map(|card| card.unwrap_or_else(|| fill.next().unwrap()))
+            .collect()
+    }
+}
+
+/// How to explore the completions of a [`PartialHand`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Try every completion.
+    Exhaustive,
+    /// Try `samples` random completions, generated from `seed`.
+    MonteCarlo { samples: u64, seed: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisError {
+    /// The partial hand holds a card that is not in the ruleset, so it was
+    /// parsed under another one.
+    UnknownCard(char),
+    /// The partial hand or the opponent does not hold as many cards as the
+    /// ruleset deals.
+    HandSize { expected: usize, found: usize },
+    /// There are more than [`MAX_EXHAUSTIVE_COMPLETIONS`] completions to try
+    /// exhaustively.
+    TooManyCompletions,
+}
+
+/// How often the completions of a [`PartialHand`] got each [`HandType`], and
+/// how they fared against an opponent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Estimate {
+    /// The number of completions that were tried.
+    pub trials: u64,
+    pub type_counts: BTreeMap<HandType, u64>,
+    pub wins: u64,
+    pub ties: u64,
+    pub losses: u64,
+}
+
+impl Estimate {
+    fn record(&mut self, hand: Hand, opponent: Option<&Hand>) {
+        self.trials += 1;
+        if let Some(opponent) = opponent {
+            match hand.cmp(opponent) {
+                Ordering::Greater => self.wins += 1,
+                Ordering::Equal => self.ties += 1,
+                Ordering::Less => self.losses += 1,
+            }
+        }
+        *self.type_counts.entry(hand.hand_type).or_insert(0) += 1;
+    }
+
+    fn fraction(&self, count: u64) -> f64 {
+        if self.trials == 0 {
+            0.0
+        } else {
+            count as f64 / self.trials as f64
+        }
+    }
+
+    /// The probability of ending up with a hand of the given type.
+    pub fn type_probability(&self, hand_type: &HandType) -> f64 {
+        self.fraction(self.type_counts.get(hand_type).copied().unwrap_or(0))
+    }
+
+    /// The probability of beating the opponent.
+    pub fn win_probability(&self) -> f64 {
+        self.fraction(self.wins)
+    }
+
+    pub fn tie_probability(&self) -> f64 {
+        self.fraction(self.ties)
+    }
+
+    pub fn loss_probability(&self) -> f64 {
+        self.fraction(self.losses)
+    }
+}
+
+/// A small, seedable random number generator (SplitMix64), so that sampled
+/// estimates can be reproduced.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`. The bias is negligible for small bounds.
+    fn below(&mut self, bound: usize) -> usize {
+        ((self.next() as u128 * bound as u128) >> 64) as usize
+    }
+}
+
+/// Completes `partial` with every card of `ruleset` equally likely in each
+/// unknown place, and counts the hand types of the completions, and how they
+/// compare to `opponent` if given. The partial hand and the opponent must be
+/// dealt under `ruleset`, see [`AnalysisError`].
+pub fn analyze(
+    partial: &PartialHand,
+    ruleset: &Ruleset,
+    opponent: Option<&Hand>,
+    method: Method,
+) -> Result<Estimate, AnalysisError> {
+    let sizes = iter::once(partial.cards.len())
+        .chain(opponent.map(|hand| hand.hand_string().chars().count()));
+    for size in sizes {
+        if size != ruleset.hand_size {
+            return Err(AnalysisError::HandSize {
+                expected: ruleset.hand_size,
+                found: size,
+            });
+        }
+    }
+
+    if let Some(card) = partial
+        .cards
+        .iter()
+        .flatten()
+        .find(|c| ruleset.card_value(**c).is_none())
+    {
+        return Err(AnalysisError::UnknownCard(*card));
+    }
+
+    let cards: Vec<char> = ruleset.card_order.chars().collect();
+    let unknown_count = partial.unknown_count();
+    let mut estimate = Estimate::default();
+
+    match method {
+        Method::Exhaustive => {
+            match partial.completion_count(ruleset) {
+                Some(count) if count <= MAX_EXHAUSTIVE_COMPLETIONS => {}
+                _ => return Err(AnalysisError::TooManyCompletions),
+            }
+
+            // count through the completions like an odometer
+            let mut indices = vec![0; unknown_count];
+            loop {
+                let hand = partial.complete(indices.iter().map(|&i| cards[i]));
+                estimate.record(Hand::new(&hand, ruleset, 0), opponent);
+
+                let Some(place) = indices.iter().rposition(|&i| i + 1 < cards.len()) else {
+                    break;
+                };
+                indices[place] += 1;
+                indices[(place + 1)..].fill(0);
+            }
+        }
+        Method::MonteCarlo { samples, seed } => {
+            let mut rng = SplitMix64(seed);
+            for _ in 0..samples {
+                let hand =
+                    partial.complete((0..unknown_count).map(|_| cards[rng.below(cards.len())]));
+                estimate.record(Hand::new(&hand, ruleset, 0), opponent);
+            }
+        }
+    }
+
+    Ok(estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand_type(counts: &[usize]) -> HandType {
+        HandType::from_counts(counts.iter().copied())
+    }
+
+    #[test]
+    fn parse_partial_hands() {
+        let ruleset = Ruleset::standard();
+        let partial = PartialHand::parse("K?K", &ruleset).unwrap();
+        assert_eq!(partial.unknown_count(), 3);
+        assert_eq!(partial.completion_count(&ruleset), Some(13 * 13 * 13));
+
+        let err = PartialHand::parse("KX", &ruleset).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::Unknown("card"));
+        assert_eq!(err.column, 2);
+        assert!(PartialHand::parse("KKKKKK", &ruleset).is_err());
+    }
+
+    #[test]
+    fn exhaustive() {
+        let ruleset = Ruleset::standard();
+        let partial = PartialHand::parse("AAAA?", &ruleset).unwrap();
+        let opponent = Hand::new("AAAAK", &ruleset, 0);
+        let estimate = analyze(&partial, &ruleset, Some(&opponent), Method::Exhaustive).unwrap();
+
+        assert_eq!(estimate.trials, 13);
+        assert_eq!(estimate.type_counts[&hand_type(&[5])], 1);
+        assert_eq!(estimate.type_counts[&hand_type(&[4, 1])], 12);
+        assert_eq!((estimate.wins, estimate.ties, estimate.losses), (1, 1, 11));
+        assert_eq!(estimate.win_probability(), 1.0 / 13.0);
+    }
+
+    #[test]
+    fn exhaustive_with_jokers() {
+        let ruleset = Ruleset::jokers();
+        let partial = PartialHand::parse("J?", &ruleset).unwrap();
+        let estimate = analyze(&partial, &ruleset, None, Method::Exhaustive).unwrap();
+
+        // the joker pairs up with any other card
+        assert_eq!(estimate.trials, 13 * 13 * 13 * 13);
+        assert_eq!(estimate.type_probability(&hand_type(&[1, 1, 1, 1, 1])), 0.0);
+        assert!(estimate.type_probability(&hand_type(&[2, 1, 1, 1])) > 0.0);
+        assert!(estimate.type_probability(&hand_type(&[5])) > 0.0);
+        let total: u64 = estimate.type_counts.values().sum();
+        assert_eq!(total, estimate.trials);
+
+        let known = PartialHand::parse("KKJ77", &ruleset).unwrap();
+        let estimate = analyze(&known, &ruleset, None, Method::Exhaustive).unwrap();
+        assert_eq!(estimate.trials, 1);
+        assert_eq!(estimate.type_probability(&hand_type(&[3, 2])), 1.0);
+    }
+
+    #[test]
+    fn monte_carlo_agrees_with_exhaustive() {
+        let ruleset = Ruleset::standard();
+        let partial = PartialHand::parse("T??", &ruleset).unwrap();
+        let opponent = Hand::new("TT432", &ruleset, 0);
+
+        let exact = analyze(&partial, &ruleset, Some(&opponent), Method::Exhaustive).unwrap();
+        let sampled = analyze(
+            &partial,
+            &ruleset,
+            Some(&opponent),
+            Method::MonteCarlo {
+                samples: 20_000,
+                seed: 2023,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(sampled.trials, 20_000);
+        assert!((exact.win_probability() - sampled.win_probability()).abs() < 0.02);
+        for hand_type in exact.type_counts.keys() {
+            let difference =
+                exact.type_probability(hand_type) - sampled.type_probability(hand_type);
+            assert!(difference.abs() < 0.02, "{}: {}", hand_type, difference);
+        }
+
+        let again = analyze(
+            &partial,
+            &ruleset,
+            Some(&opponent),
+            Method::MonteCarlo {
+                samples: 20_000,
+                seed: 2023,
+            },
+        )
+        .unwrap();
+        assert_eq!(sampled, again);
+    }
+
+    #[test]
+    fn analysis_errors() {
+        let jokers = Ruleset::jokers();
+        let with_x = Ruleset {
+            card_order: "XJ23456789TQKA".to_owned(),
+            ..jokers.clone()
+        };
+        let partial = PartialHand::parse("X?", &with_x).unwrap();
+        assert_eq!(
+            analyze(&partial, &jokers, None, Method::Exhaustive),
+            Err(AnalysisError::UnknownCard('X'))
+        );
+
+        let six_cards = Ruleset {
+            hand_size: 6,
+            ..jokers.clone()
+        };
+        let partial = PartialHand::parse("KK?", &jokers).unwrap();
+        assert_eq!(
+            analyze(&partial, &six_cards, None, Method::Exhaustive),
+            Err(AnalysisError::HandSize {
+                expected: 6,
+                found: 5
+            })
+        );
+        let opponent = Hand::new("KKKKKK", &six_cards, 0);
+        assert_eq!(
+            analyze(&partial, &jokers, Some(&opponent), Method::Exhaustive),
+            Err(AnalysisError::HandSize {
+                expected: 5,
+                found: 6
+            })
+        );
+
+        let big = Ruleset {
+            hand_size: 9,
+            ..jokers
+        };
+        let partial = PartialHand::parse("", &big).unwrap();
+        assert_eq!(partial.completion_count(&big), Some(13u64.pow(9)));
+        assert_eq!(
+            analyze(&partial, &big, None, Method::Exhaustive),
+            Err(AnalysisError::TooManyCompletions)
+        );
+        let sampled = analyze(
+            &partial,
+            &big,
+            None,
+            Method::MonteCarlo {
+                samples: 100,
+                seed: 1,
+            },
+        );
+        assert_eq!(sampled.map(|estimate| estimate.trials), Ok(100));
+    }
+}
@@ -1,25 +1,91 @@
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 use std::collections::HashMap;
-use std::time::Instant;
+use std::fmt;
 
-use crate::utils;
+use crate::error::{ParseError, ParseErrorKind, SolveError};
+use crate::solver::{answer_from_u64, Answer, Solver};
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
-    FiveOfAKind = 6,
-    FourOfAKind = 5,
-    FullHouse = 4,
-    ThreeOfAKind = 3,
-    TwoPair = 2,
-    OnePair = 1,
-    HighCard = 0,
+mod analysis;
+mod ruleset;
+
+pub use analysis::{
+    analyze, AnalysisError, Estimate, Method, PartialHand, MAX_EXHAUSTIVE_COMPLETIONS, UNKNOWN_CARD,
+};
+pub use ruleset::{Ruleset, TieBreak};
+
+/// Names of hand types, by how many of each card they have, leaving out the
+/// cards that appear once. A hand type that is not listed is still ranked,
+/// but is named by its counts.
+pub const HAND_CATEGORIES: &[(&[usize], &str)] = &[
+    (&[6], "six of a kind"),
+    (&[5], "five of a kind"),
+    (&[4, 2], "four of a kind and a pair"),
+    (&[4], "four of a kind"),
+    (&[3, 3], "two triples"),
+    (&[3, 2], "full house"),
+    (&[3], "three of a kind"),
+    (&[2, 2, 2], "three pairs"),
+    (&[2, 2], "two pair"),
+    (&[2], "one pair"),
+    (&[], "high card"),
+];
+
+/// The type of a hand: how many of each card it has, from most to least
+/// common. Types are ordered by comparing the counts in turn, so with five
+/// cards, five of a kind `[5]` beats four of a kind `[4, 1]`, which beats a
+/// full house `[3, 2]`, and so on. This works for hands of any size.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandType {
+    counts: Vec<usize>,
 }
 
-#[derive(Debug)]
-struct Hand {
+impl HandType {
+    pub fn from_counts<I: IntoIterator<Item = usize>>(counts: I) -> Self {
+        let mut counts: Vec<usize> = counts.into_iter().filter(|&c| c > 0).collect();
+        counts.sort_by(|a, b| b.cmp(a));
+        HandType { counts }
+    }
+
+    /// How many of each card the hand has, from most to least common.
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    /// The name of the type from [`HAND_CATEGORIES`], or the counts joined by
+    /// `-` if it has none.
+    pub fn name(&self) -> String {
+        let groups: Vec<usize> = self.counts.iter().copied().filter(|&c| c > 1).collect();
+        match HAND_CATEGORIES.iter().find(|(counts, _)| *counts == groups) {
+            Some((_, name)) => name.to_string(),
+            None => self
+                .counts
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<String>>()
+                .join("-"),
+        }
+    }
+}
+
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A Camel Cards hand. Hands are ordered by their [`HandType`], and then by
+/// their cards, as described by the [`Ruleset`] they were dealt under.
+#[derive(Debug, Clone)]
+pub struct Hand {
     hand_string: String,
     hand_type: HandType,
-    use_joker: bool,
+    /// The card the wildcards in the hand act as.
+    substitution: Option<char>,
+    /// The card values, in the order the ruleset compares them in.
+    tie_break_values: Vec<usize>,
+    /// The cards behind `tie_break_values`, in the same order.
+    tie_break_cards: Vec<char>,
+    tie_break: TieBreak,
     bet: u32,
 }
 
@@ -27,31 +93,15 @@ impl Eq for Hand {}
 
 impl PartialEq for Hand {
     fn eq(&self, other: &Hand) -> bool {
-        self.hand_string == other.hand_string && self.hand_type == other.hand_type
+        self.hand_type == other.hand_type && self.tie_break_values == other.tie_break_values
     }
 }
 
 impl Ord for Hand {
     fn cmp(&self, other: &Self) -> Ordering {
-        if self.eq(other) {
-            return Ordering::Equal;
-        }
-
-        if self.hand_type != other.hand_type {
-            return self.hand_type.cmp(&other.hand_type);
-        }
-
-        for (c1, c2) in self.hand_string.chars().zip(other.hand_string.chars()) {
-            match (self.map_card(&c1), other.map_card(&c2)) {
-                (Some(v1), Some(v2)) if v1 != v2 => {
-                    return v1.cmp(v2);
-                }
-                (Some(_), Some(_)) => {}
-                _ => panic!("Got non-card chars! {:?}, {:?}", c1, c2),
-            }
-        }
-
-        Ordering::Equal
+        self.hand_type
+            .cmp(&other.hand_type)
+            .then_with(|| self.tie_break_values.cmp(&other.tie_break_values))
     }
 }
 
@@ -62,123 +112,521 @@ impl PartialOrd for Hand {
 }
 
 impl Hand {
-    const NORMAL_CARD_VALUE_MAP: phf::Map<char, u8> = phf::phf_map! {
-        'A' => 12, 'K' => 11, 'Q' => 10, 'J' => 9, 'T' => 8,
-        '9' => 7, '8' => 6, '7' => 5, '6' => 4, '5' => 3,
-        '4' => 2, '3' => 1, '2' => 0,
-    };
-
-    const JOKER_CARD_VALUE_MAP: phf::Map<char, u8> = phf::phf_map! {
-        'A' => 12, 'K' => 11, 'Q' => 10, 'T' => 9, '9' => 8,
-        '8' => 7, '7' => 6, '6' => 5, '5' => 4, '4' => 3,
-        '3' => 2, '2' => 1, 'J' => 0,
-    };
-
-    fn get_hand_type(hand_string: &str, use_joker: bool) -> HandType {
-        let mut hand_map = HashMap::<char, u8>::new();
+    /// Finds the type of a hand, and the card its wildcards act as, if it has
+    /// any.
+    fn get_hand_type(hand_string: &str, ruleset: &Ruleset) -> (HandType, Option<char>) {
+        let mut hand_map = HashMap::<char, usize>::new();
+        let mut wild_count = 0;
         for c in hand_string.chars() {
-            hand_map.entry(c).and_modify(|v| *v += 1).or_insert(1);
-        }
-
-        // We always want the joker to be the most common card
-        if use_joker && hand_map.contains_key(&'J') {
-            let joker_count = *hand_map.get(&'J').unwrap();
-
-            if joker_count as usize != hand_string.len() {
-                let (max_card, _) = hand_map
-                    .iter()
-                    .filter(|(k, _)| **k != 'J')
-                    .max_by_key(|(_, v)| *v)
-                    .expect("there is no max card‽");
-                hand_map.entry(*max_card).and_modify(|v| *v += joker_count);
+            if ruleset.is_wild(c) {
+                wild_count += 1;
             } else {
-                hand_map.insert('A', joker_count);
+                hand_map.entry(c).and_modify(|v| *v += 1).or_insert(1);
             }
-
-            hand_map.remove(&'J');
         }
 
-        match hand_map.len() {
-            1 => HandType::FiveOfAKind,
-            2 => {
-                if *hand_map.values().max().unwrap() == 4 {
-                    HandType::FourOfAKind
-                } else {
-                    HandType::FullHouse
-                }
-            }
-            3 => {
-                if *hand_map.values().max().unwrap() == 3 {
-                    HandType::ThreeOfAKind
-                } else {
-                    HandType::TwoPair
-                }
-            }
-            4 => HandType::OnePair,
-            5 => HandType::HighCard,
-            _ => panic!("hand_map.len() is 0 or > 5! {:?}", hand_map),
-        }
+        // We always want the wildcards to be the most common card, and the
+        // strongest one if there is a tie. If every card is wild, any card will do.
+        let substitution = hand_map
+            .iter()
+            .max_by_key(|(c, count)| (**count, ruleset.card_value(**c)))
+            .map(|(c, _)| *c)
+            .or_else(|| {
+                ruleset
+                    .card_order
+                    .chars()
+                    .rev()
+                    .find(|c| !ruleset.is_wild(*c))
+            });
+        *hand_map.entry(substitution.unwrap_or('*')).or_insert(0) += wild_count;
+
+        let substitution = substitution.filter(|_| wild_count > 0);
+        (HandType::from_counts(hand_map.into_values()), substitution)
     }
 
-    fn new(hand_string: &str, use_joker: bool, bet: u32) -> Self {
+    /// Creates a hand. Panics if `hand_string` holds a card that is not in the ruleset.
+    pub fn new(hand_string: &str, ruleset: &Ruleset, bet: u32) -> Self {
+        let mut tie_break_cards: Vec<(usize, char)> = hand_string
+            .chars()
+            .map(|c| {
+                let value = ruleset
+                    .card_value(c)
+                    .unwrap_or_else(|| panic!("Got non-card char! {:?}", c));
+                (value, c)
+            })
+            .collect();
+        if ruleset.tie_break == TieBreak::Strongest {
+            tie_break_cards.sort_by_key(|&(value, _)| Reverse(value));
+        }
+        let (tie_break_values, tie_break_cards) = tie_break_cards.into_iter().unzip();
+
+        let (hand_type, substitution) = Hand::get_hand_type(hand_string, ruleset);
         Hand {
             hand_string: hand_string.to_owned(),
-            hand_type: Hand::get_hand_type(hand_string, use_joker),
-            use_joker,
+            hand_type,
+            substitution,
+            tie_break_values,
+            tie_break_cards,
+            tie_break: ruleset.tie_break,
             bet,
         }
     }
 
-    fn map_card(&self, card: &char) -> Option<&u8> {
-        if self.use_joker {
-            Hand::JOKER_CARD_VALUE_MAP.get(card)
+    /// Parses a `<hand> <bet>` line. Errors point into 'string'.
+    pub fn from_string(string: &str, ruleset: &Ruleset) -> Result<Self, ParseError> {
+        let (hand, bet) = split_deal(string)?;
+        check_cards(string, hand, ruleset)?;
+        Ok(Hand::new(hand, ruleset, bet))
+    }
+
+    pub fn hand_string(&self) -> &str {
+        &self.hand_string
+    }
+
+    pub fn hand_type(&self) -> &HandType {
+        &self.hand_type
+    }
+
+    /// The card that the wildcards in the hand act as, or `None` if the hand
+    /// has no wildcards.
+    pub fn substitution(&self) -> Option<char> {
+        self.substitution
+    }
+
+    pub fn bet(&self) -> u32 {
+        self.bet
+    }
+
+    /// Compares two hands, and says what decided it. The hands should be
+    /// dealt under the same ruleset.
+    pub fn explain_cmp(&self, other: &Hand) -> Comparison {
+        let reason = if self.hand_type != other.hand_type {
+            Reason::HandType
         } else {
-            Hand::NORMAL_CARD_VALUE_MAP.get(card)
+            match self
+                .tie_break_values
+                .iter()
+                .zip(other.tie_break_values.iter())
+                .position(|(a, b)| a != b)
+            {
+                Some(position) => Reason::Card {
+                    position,
+                    cards: (
+                        self.tie_break_cards[position],
+                        other.tie_break_cards[position],
+                    ),
+                    tie_break: self.tie_break,
+                },
+                None => Reason::Tie,
+            }
+        };
+
+        Comparison {
+            ordering: self.cmp(other),
+            hands: (self.clone(), other.clone()),
+            reason,
+        }
+    }
+}
+
+/// What decided a [`Comparison`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The hands have different types.
+    HandType,
+    /// The hands have the same type, and the first differing card decided.
+    Card {
+        /// Where the card is among the cards being compared, starting at 0.
+        position: usize,
+        cards: (char, char),
+        tie_break: TieBreak,
+    },
+    /// The hands have the same type and equally strong cards.
+    Tie,
+}
+
+/// The comparison of two hands, made by [`Hand::explain_cmp`].
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// How the first hand compares to the second.
+    pub ordering: Ordering,
+    pub hands: (Hand, Hand),
+    pub reason: Reason,
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (a, b) = &self.hands;
+        let verb = match self.ordering {
+            Ordering::Greater => "beats",
+            Ordering::Less => "loses to",
+            Ordering::Equal => "ties with",
+        };
+        write!(f, "{} {} {}: ", a.hand_string, verb, b.hand_string)?;
+
+        match &self.reason {
+            Reason::HandType => write!(f, "{} against {}", a.hand_type, b.hand_type),
+            Reason::Card {
+                position,
+                cards: (card_a, card_b),
+                tie_break,
+            } => {
+                let place = match tie_break {
+                    TieBreak::InOrder => "card",
+                    TieBreak::Strongest => "strongest card",
+                };
+                write!(
+                    f,
+                    "both are {}, and {} {} is {} against {}",
+                    a.hand_type,
+                    place,
+                    position + 1,
+                    card_a,
+                    card_b
+                )
+            }
+            Reason::Tie => write!(f, "both are {} with equally strong cards", a.hand_type),
+        }
+    }
+}
+
+/// Splits a `<hand> <bet>` line into the cards of the hand and the bet.
+/// Errors point into 'string'.
+fn split_deal(string: &str) -> Result<(&str, u32), ParseError> {
+    let (hand, bet_str) = match string.split(' ').collect::<Vec<&str>>()[..] {
+        [hand, bet_str] => (hand, bet_str),
+        _ => {
+            return Err(ParseError::at(
+                string,
+                string,
+                ParseErrorKind::Expected("`<hand> <bet>`"),
+            ))
         }
+    };
+
+    let bet = bet_str
+        .parse()
+        .map_err(|_| ParseError::at(string, bet_str, ParseErrorKind::InvalidNumber))?;
+    Ok((hand, bet))
+}
+
+/// Checks that `hand` holds as many cards as the ruleset deals, and only cards
+/// of the ruleset. Errors point into 'string', which `hand` is part of.
+fn check_cards(string: &str, hand: &str, ruleset: &Ruleset) -> Result<(), ParseError> {
+    if hand.chars().count() != ruleset.hand_size {
+        return Err(ParseError::at(
+            string,
+            hand,
+            ParseErrorKind::Expected("as many cards as the hand size of the ruleset"),
+        ));
     }
+
+    if let Some((i, c)) = hand
+        .char_indices()
+        .find(|(_, c)| ruleset.card_value(*c).is_none())
+    {
+        return Err(ParseError::at(
+            string,
+            &hand[i..(i + c.len_utf8())],
+            ParseErrorKind::Unknown("card"),
+        ));
+    }
+
+    Ok(())
 }
 
-fn get_total_winnings(filename: &str, use_joker: bool) -> u32 {
-    let mut hands: Vec<Hand> = utils::read_lines(filename)
-        .map(|line| match line.split(' ').collect::<Vec<&str>>()[..] {
-            [hand, bet_str] => Hand::new(
-                hand,
-                use_joker,
-                bet_str
-                    .parse::<u32>()
-                    .unwrap_or_else(|_| panic!("malformed bet_str: {}", bet_str)),
-            ),
-            _ => panic!("malformed line: {:?}", line),
+/// The cards and bet of a hand, before a ruleset decides how strong it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deal {
+    pub cards: String,
+    pub bet: u32,
+}
+
+/// Parses every `<hand> <bet>` line in `input`, checking the cards against
+/// `ruleset`. The deals can be ranked under any ruleset with the same cards
+/// and hand size.
+pub fn get_deals(input: &str, ruleset: &Ruleset) -> Result<Vec<Deal>, ParseError> {
+    input
+        .lines()
+        .map(|line| {
+            let (hand, bet) = split_deal(line).map_err(|err| err.within(input, line))?;
+            check_cards(line, hand, ruleset).map_err(|err| err.within(input, line))?;
+            Ok(Deal {
+                cards: hand.to_owned(),
+                bet,
+            })
         })
-        .collect::<Vec<Hand>>();
+        .collect()
+}
+
+/// A hand and where it placed.
+#[derive(Debug, Clone)]
+pub struct RankedHand {
+    /// The rank of the hand, starting at 1 for the weakest hand.
+    pub rank: usize,
+    pub hand: Hand,
+    /// The bet of the hand times its rank.
+    pub winnings: u64,
+}
+
+/// Ranks every deal under `ruleset`, from weakest to strongest. Panics if a
+/// deal holds a card that is not in the ruleset, see [`get_deals`].
+pub fn rank_deals(deals: &[Deal], ruleset: &Ruleset) -> Vec<RankedHand> {
+    let mut hands: Vec<Hand> = deals
+        .iter()
+        .map(|deal| Hand::new(&deal.cards, ruleset, deal.bet))
+        .collect();
 
     hands.sort();
 
     hands
         .into_iter()
         .enumerate()
-        .map(|(rank, hand)| (rank + 1) as u32 * hand.bet)
-        .sum()
+        .map(|(i, hand)| RankedHand {
+            rank: i + 1,
+            winnings: (i + 1) as u64 * hand.bet as u64,
+            hand,
+        })
+        .collect()
 }
 
-fn test() {
-    assert_eq!(get_total_winnings("src/d7/test_input.dat", false), 6440);
-    assert_eq!(get_total_winnings("src/d7/test_input.dat", true), 5905);
+/// Parses and ranks every hand in `input`, from weakest to strongest.
+pub fn rank_hands(input: &str, ruleset: &Ruleset) -> Result<Vec<RankedHand>, ParseError> {
+    Ok(rank_deals(&get_deals(input, ruleset)?, ruleset))
 }
 
-pub fn test_final() {
-    assert_eq!(get_total_winnings("src/d7/full_input.dat", false), 251136060);
-    assert_eq!(get_total_winnings("src/d7/full_input.dat", true), 249400220);
+pub fn get_total_winnings(deals: &[Deal], ruleset: &Ruleset) -> u64 {
+    rank_deals(deals, ruleset)
+        .into_iter()
+        .map(|ranked| ranked.winnings)
+        .sum()
 }
 
-pub fn main() {
-    test();
+pub struct Day7;
+
+impl Solver for Day7 {
+    type Input = Vec<Deal>;
+
+    const DAY: u32 = 7;
+    const TITLE: &'static str = "Camel Cards";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        // the joker rules only reorder the cards, so the deals work for both
+        get_deals(input, &Ruleset::standard())
+    }
+
+    fn part_one(&self, deals: &Self::Input) -> Result<Answer, SolveError> {
+        answer_from_u64(get_total_winnings(deals, &Ruleset::standard()))
+    }
+
+    fn part_two(&self, deals: &Self::Input) -> Result<Answer, SolveError> {
+        answer_from_u64(get_total_winnings(deals, &Ruleset::jokers()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn part_one_example() {
+        let input = utils::read_input("src/d7/test_input.dat");
+        let deals = get_deals(&input, &Ruleset::standard()).unwrap();
+        assert_eq!(get_total_winnings(&deals, &Ruleset::standard()), 6440);
+    }
+
+    #[test]
+    fn part_two_example() {
+        let input = utils::read_input("src/d7/test_input.dat");
+        let deals = get_deals(&input, &Ruleset::jokers()).unwrap();
+        assert_eq!(get_total_winnings(&deals, &Ruleset::jokers()), 5905);
+    }
+
+    #[test]
+    fn large_bets() {
+        let ranked =
+            rank_hands("23456 4294967295\nAAAAA 4294967295", &Ruleset::standard()).unwrap();
+        assert_eq!(ranked[1].winnings, 2 * 4294967295);
+        let deals = get_deals("23456 4294967295\nAAAAA 4294967295", &Ruleset::standard()).unwrap();
+        assert_eq!(
+            get_total_winnings(&deals, &Ruleset::standard()),
+            3 * 4294967295
+        );
+    }
+
+    #[test]
+    fn unknown_card() {
+        let err = Hand::from_string("32T3X 765", &Ruleset::standard()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::Unknown("card"));
+        assert_eq!(err.column, 5);
+    }
+
+    #[test]
+    fn house_rules() {
+        let ruleset = Ruleset::load("src/d7/house_rules.toml").unwrap();
+        assert_eq!(ruleset.wildcards, "JQ");
+        assert_eq!(ruleset.tie_break, TieBreak::Strongest);
+
+        let hand = |string| Hand::new(string, &ruleset, 0);
+        assert_eq!(hand("QJ234").hand_type().name(), "three of a kind");
+        assert_eq!(hand("QQQQJ").hand_type().name(), "five of a kind");
+        // the strongest card decides, not the first one
+        assert!(hand("2345A") > hand("K6789"));
+        assert!(
+            Hand::new("2345A", &Ruleset::standard(), 0)
+                < Hand::new("K6789", &Ruleset::standard(), 0)
+        );
+    }
+
+    #[test]
+    fn invalid_rulesets() {
+        assert!(Ruleset::from_toml("card_order = \"23452\"").is_err());
+        assert!(Ruleset::from_toml("card_order = \"2345\"\nwildcards = \"J\"").is_err());
+        assert!(Ruleset::from_toml("card_order = \"2345\"\ntie_break = \"random\"").is_err());
+        assert_eq!(
+            Ruleset::from_toml("card_order = \"23456789TJQKA\""),
+            Ok(Ruleset::standard())
+        );
+    }
+
+    #[test]
+    fn five_card_types() {
+        let types: Vec<String> = [
+            "AAAAA", "AA8AA", "23332", "TTT98", "23432", "A23A4", "23456",
+        ]
+        .into_iter()
+        .map(|string| {
+            Hand::new(string, &Ruleset::standard(), 0)
+                .hand_type()
+                .name()
+        })
+        .collect();
+        assert_eq!(
+            types,
+            [
+                "five of a kind",
+                "four of a kind",
+                "full house",
+                "three of a kind",
+                "two pair",
+                "one pair",
+                "high card"
+            ]
+        );
+    }
+
+    #[test]
+    fn hand_types_are_ordered_by_counts() {
+        let types: Vec<HandType> = [
+            vec![1, 1, 1, 1, 1],
+            vec![2, 1, 1, 1],
+            vec![1, 2, 2],
+            vec![3, 1, 1],
+            vec![2, 3],
+            vec![1, 4],
+            vec![5],
+        ]
+        .into_iter()
+        .map(HandType::from_counts)
+        .collect();
+        assert!(types.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn six_card_hands() {
+        let ruleset = Ruleset {
+            hand_size: 6,
+            ..Ruleset::jokers()
+        };
+        let hand = |string| Hand::from_string(string, &ruleset).unwrap();
+
+        assert_eq!(hand("KKKKKJ 1").hand_type().name(), "six of a kind");
+        assert_eq!(hand("223344 1").hand_type().name(), "three pairs");
+        assert_eq!(hand("22334J 1").hand_type().name(), "full house");
+        assert_eq!(hand("222333 1").hand_type().name(), "two triples");
+        assert_eq!(hand("23456A 1").hand_type().name(), "high card");
+        assert!(hand("222333 1") > hand("223344 1"));
+        assert!(hand("2222A3 1") > hand("AAA333 1"));
+        assert!(Hand::from_string("22334 1", &ruleset).is_err());
 
-    let mut now = Instant::now();
-    let sum_p1 = get_total_winnings("src/d7/full_input.dat", false);
-    println!("Part one result: {} (took {:?})", sum_p1, now.elapsed());
+        let big = Ruleset {
+            hand_size: 9,
+            ..Ruleset::standard()
+        };
+        let hand = Hand::from_string("222333444 1", &big).unwrap();
+        assert_eq!(hand.hand_type().name(), "3-3-3");
+    }
+
+    #[test]
+    fn ranked_table() {
+        let input = utils::read_input("src/d7/test_input.dat");
+        let table = rank_hands(&input, &Ruleset::jokers()).unwrap();
+
+        let rows: Vec<(usize, &str, String, Option<char>, u64)> = table
+            .iter()
+            .map(|ranked| {
+                (
+                    ranked.rank,
+                    ranked.hand.hand_string(),
+                    ranked.hand.hand_type().name(),
+                    ranked.hand.substitution(),
+                    ranked.winnings,
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (1, "32T3K", "one pair".to_owned(), None, 765),
+                (2, "KK677", "two pair".to_owned(), None, 56),
+                (3, "T55J5", "four of a kind".to_owned(), Some('5'), 2052),
+                (4, "QQQJA", "four of a kind".to_owned(), Some('Q'), 1932),
+                (5, "KTJJT", "four of a kind".to_owned(), Some('T'), 1100),
+            ]
+        );
+    }
 
-    now = Instant::now();
-    let sum_p2 = get_total_winnings("src/d7/full_input.dat", true);
-    println!("Part two result: {} (took {:?})", sum_p2, now.elapsed());
+    #[test]
+    fn substitution_prefers_the_strongest_card() {
+        let ruleset = Ruleset::jokers();
+        assert_eq!(Hand::new("23J32", &ruleset, 0).substitution(), Some('3'));
+        assert_eq!(Hand::new("JJJJJ", &ruleset, 0).substitution(), Some('A'));
+    }
+
+    #[test]
+    fn explain_comparisons() {
+        let ruleset = Ruleset::jokers();
+        let hand = |string| Hand::new(string, &ruleset, 0);
+        let explain = |a, b| hand(a).explain_cmp(&hand(b));
+
+        let comparison = explain("KTJJT", "QQQJA");
+        assert_eq!(comparison.ordering, Ordering::Greater);
+        assert_eq!(
+            comparison.reason,
+            Reason::Card {
+                position: 0,
+                cards: ('K', 'Q'),
+                tie_break: TieBreak::InOrder
+            }
+        );
+        assert_eq!(
+            comparison.to_string(),
+            "KTJJT beats QQQJA: both are four of a kind, and card 1 is K against Q"
+        );
+
+        assert_eq!(
+            explain("32T3K", "KK677").to_string(),
+            "32T3K loses to KK677: one pair against two pair"
+        );
+        assert_eq!(explain("KK677", "KK677").reason, Reason::Tie);
+
+        let strongest = Ruleset::load("src/d7/house_rules.toml").unwrap();
+        let comparison =
+            Hand::new("2345A", &strongest, 0).explain_cmp(&Hand::new("K6789", &strongest, 0));
+        assert_eq!(
+            comparison.to_string(),
+            "2345A beats K6789: both are high card, and strongest card 1 is A against K"
+        );
+    }
 }
@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+/// How to break a tie between two hands of the same [`HandType`](super::HandType).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TieBreak {
+    /// Compare the cards in the order they were dealt, as in the puzzle.
+    #[default]
+    InOrder,
+    /// Compare the cards from strongest to weakest, as in poker.
+    Strongest,
+}
+
+/// The rules of a game of Camel Cards: which cards there are, how strong
+/// they are, and which of them are wild.
+///
+/// A ruleset can be loaded from a TOML file such as
+///
+/// ```toml
+/// # the cards, from weakest to strongest
+/// card_order = "J23456789TQKA"
+/// # cards that act as whatever card makes the strongest hand
+/// wildcards = "J"
+/// # "in-order" (default) or "strongest"
+/// tie_break = "in-order"
+/// # the number of cards in a hand (default: 5)
+/// hand_size = 5
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Ruleset {
+    /// Every card, from weakest to strongest.
+    pub card_order: String,
+    /// Cards that act as whatever card makes the strongest hand. When breaking
+    /// ties, they keep their place in [`Ruleset::card_order`].
+    #[serde(default)]
+    pub wildcards: String,
+    #[serde(default)]
+    pub tie_break: TieBreak,
+    /// The number of cards in a hand.
+    #[serde(default = "default_hand_size")]
+    pub hand_size: usize,
+}
+
+fn default_hand_size() -> usize {
+    5
+}
+
+impl Ruleset {
+    /// The rules of part one.
+    pub fn standard() -> Self {
+        Ruleset {
+            card_order: "23456789TJQKA".to_owned(),
+            wildcards: String::new(),
+            tie_break: TieBreak::InOrder,
+            hand_size: default_hand_size(),
+        }
+    }
+
+    /// The rules of part two, where jacks are jokers.
+    pub fn jokers() -> Self {
+        Ruleset {
+            card_order: "J23456789TQKA".to_owned(),
+            wildcards: "J".to_owned(),
+            tie_break: TieBreak::InOrder,
+            hand_size: default_hand_size(),
+        }
+    }
+
+    /// Parses and validates a ruleset.
+    pub fn from_toml(string: &str) -> Result<Self, String> {
+        let ruleset: Ruleset = toml::from_str(string).map_err(|err| err.to_string())?;
+        ruleset.validate()?;
+        Ok(ruleset)
+    }
+
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Self, String> {
+        let path = path.as_ref();
+        let string = fs::read_to_string(path)
+            .map_err(|err| format!("Could not read {:?}: {}", path, err))?;
+        Ruleset::from_toml(&string).map_err(|err| format!("Invalid ruleset {:?}: {}", path, err))
+    }
+
+    /// Checks that the cards are unique, that every wildcard is a card, and
+    /// that hands have cards.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.card_order.is_empty() {
+            return Err("there are no cards".to_owned());
+        }
+        if self.hand_size == 0 {
+            return Err("the hand size must be at least 1".to_owned());
+        }
+
+        for (i, card) in self.card_order.char_indices() {
+            if self.card_order[..i].contains(card) {
+                return Err(format!("the card {:?} is listed twice", card));
+            }
+        }
+
+        match self
+            .wildcards
+            .chars()
+            .find(|&c| !self.card_order.contains(c))
+        {
+            Some(card) => Err(format!("the wildcard {:?} is not a card", card)),
+            None => Ok(()),
+        }
+    }
+
+    /// The strength of a card, starting at 0 for the weakest card.
+    pub fn card_value(&self, card: char) -> Option<usize> {
+        self.card_order.chars().position(|c| c == card)
+    }
+
+    pub fn is_wild(&self, card: char) -> bool {
+        self.wildcards.contains(card)
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset::standard()
+    }
+}
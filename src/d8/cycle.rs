@@ -0,0 +1,199 @@
+use super::{Direction, Network, NodeId};
+use crate::utils::math::{self, Congruence, CrtError};
+
+/// The shape of a walk through a network. Since the walk is decided by the
+/// node and the index of the next instruction, it must eventually return to
+/// an earlier such state, and from then on repeat itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// The number of steps taken before the walk enters its cycle.
+    pub tail_length: u64,
+    pub cycle_length: u64,
+    /// The step counts before the cycle at which an end node is reached.
+    pub tail_hits: Vec<u64>,
+    /// The step counts in the first pass through the cycle at which an end
+    /// node is reached. They repeat every `cycle_length` steps.
+    pub cycle_hits: Vec<u64>,
+}
+
+impl Cycle {
+    /// Walks from `start` until the walk repeats itself, noting when it
+    /// reaches a node where `is_end` holds. Panics if there are no instructions.
+    pub fn find<F: Fn(NodeId) -> bool>(
+        network: &Network,
+        instructions: &[Direction],
+        start: NodeId,
+        is_end: F,
+    ) -> Self {
+        assert!(
+            !instructions.is_empty(),
+            "can not walk without instructions"
+        );
+
+        // the step count at which each (node, instruction index) state was first seen
+        let mut seen: Vec<Option<u64>> = vec![None; network.len() * instructions.len()];
+        let mut walk = network.walk(start, instructions);
+        let mut hits = vec![];
+
+        let mut step_count = 0;
+        let tail_length = loop {
+            let (node, index) = walk.state();
+            let state = &mut seen[node.index() * instructions.len() + index];
+            if let Some(first_seen) = *state {
+                break first_seen;
+            }
+            *state = Some(step_count);
+
+            if is_end(node) {
+                hits.push(step_count);
+            }
+            walk.next();
+            step_count += 1;
+        };
+
+        let split = hits.partition_point(|&hit| hit < tail_length);
+        Cycle {
+            tail_length,
+            cycle_length: step_count - tail_length,
+            cycle_hits: hits.split_off(split),
+            tail_hits: hits,
+        }
+    }
+
+    /// Whether an end node is reached after `step_count` steps.
+    pub fn is_hit(&self, step_count: u64) -> bool {
+        if step_count < self.tail_length {
+            self.tail_hits.binary_search(&step_count).is_ok()
+        } else {
+            let offset = (step_count - self.tail_length) % self.cycle_length;
+            self.cycle_hits
+                .binary_search(&(self.tail_length + offset))
+                .is_ok()
+        }
+    }
+
+    /// The fewest steps, at least one, that reach an end node.
+    pub fn first_hit(&self) -> Option<u64> {
+        // a hit at the start is only reached again if the cycle starts there
+        let tail_hit = self.tail_hits.iter().copied().find(|&hit| hit > 0);
+        let cycle_hit = self
+            .cycle_hits
+            .iter()
+            .map(|&hit| if hit == 0 { self.cycle_length } else { hit })
+            .min();
+        tail_hit.or(cycle_hit)
+    }
+}
+
+/// The fewest steps, at least one, after which every walk is at an end node
+/// at the same time.
+pub fn first_common_hit(cycles: &[Cycle]) -> Result<u64, CrtError> {
+    // before every walk has entered its cycle, just check each step
+    let periodic_from = cycles
+        .iter()
+        .map(|cycle| cycle.tail_length)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    if let Some(step_count) =
+        (1..periodic_from).find(|&step_count| cycles.iter().all(|cycle| cycle.is_hit(step_count)))
+    {
+        return Ok(step_count);
+    }
+
+    // after that, each walk is at an end node at some residues modulo its
+    // cycle length, and we look for a step count that agrees with all of them
+    let mut congruences = vec![Congruence::new(0, 1)];
+    for cycle in cycles {
+        let mut combined = vec![];
+        for &congruence in congruences.iter() {
+            for &hit in cycle.cycle_hits.iter() {
+                match math::crt(&[congruence, Congruence::new(hit, cycle.cycle_length)]) {
+                    Ok(congruence) => combined.push(congruence),
+                    Err(CrtError::NoSolution) => {}
+                    Err(CrtError::Overflow) => return Err(CrtError::Overflow),
+                }
+            }
+        }
+        congruences = combined;
+    }
+
+    let step_counts: Option<Vec<u64>> = congruences
+        .iter()
+        .map(|congruence| congruence.first_from(periodic_from))
+        .collect();
+    step_counts
+        .ok_or(CrtError::Overflow)?
+        .into_iter()
+        .min()
+        .ok_or(CrtError::NoSolution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d8::get_documents;
+
+    fn cycles(input: &str) -> Vec<Cycle> {
+        let documents = get_documents(input).unwrap();
+        let network = &documents.network;
+        network
+            .nodes()
+            .filter(|&node| network.name(node).ends_with('A'))
+            .map(|start| {
+                Cycle::find(network, &documents.steps, start, |node| {
+                    network.name(node).ends_with('Z')
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_cycles() {
+        let cycles = cycles("L\n\n1A = (1B, 1B)\n1B = (1Z, 1Z)\n1Z = (1B, 1B)");
+        assert_eq!(
+            cycles,
+            [Cycle {
+                tail_length: 1,
+                cycle_length: 2,
+                tail_hits: vec![],
+                cycle_hits: vec![2],
+            }]
+        );
+        assert!(cycles[0].is_hit(6));
+        assert!(!cycles[0].is_hit(7));
+        assert_eq!(cycles[0].first_hit(), Some(2));
+    }
+
+    #[test]
+    fn offset_cycles() {
+        // hits at 2, 4, 6, ... and at 1, 4, 7, ..., so the first hits do not
+        // line up with the cycle lengths
+        let cycles = cycles(
+            "L\n\n1A = (1B, 1B)\n1B = (1Z, 1Z)\n1Z = (1B, 1B)\n\
+             2A = (2Z, 2Z)\n2Z = (2C, 2C)\n2C = (2D, 2D)\n2D = (2Z, 2Z)",
+        );
+        assert_eq!(first_common_hit(&cycles), Ok(4));
+    }
+
+    #[test]
+    fn tail_hits() {
+        // 3Z is only reached once, on the way into the cycle
+        let cycles = cycles(
+            "L\n\n1A = (1B, 1B)\n1B = (1Z, 1Z)\n1Z = (1B, 1B)\n\
+             3A = (3B, 3B)\n3B = (3Z, 3Z)\n3Z = (3C, 3C)\n3C = (3C, 3C)",
+        );
+        assert_eq!(cycles[1].tail_hits, [2]);
+        assert!(cycles[1].cycle_hits.is_empty());
+        assert_eq!(first_common_hit(&cycles), Ok(2));
+    }
+
+    #[test]
+    fn no_common_hit() {
+        let cycles = cycles(
+            "L\n\n1A = (1Z, 1Z)\n1Z = (1B, 1B)\n1B = (1Z, 1Z)\n\
+             2A = (2B, 2B)\n2B = (2Z, 2Z)\n2Z = (2B, 2B)",
+        );
+        assert_eq!(first_common_hit(&cycles), Err(CrtError::NoSolution));
+    }
+}
@@ -0,0 +1,133 @@
+use super::{Cycle, Direction, Documents, NodeId};
+
+/// The colors of the ghost paths, reused if there are more ghosts.
+const PATH_COLORS: [&str; 6] = ["blue", "red", "darkgreen", "orange", "purple", "brown"];
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The nodes a ghost passes through from `start`, until it first reaches a
+/// node ending in `Z`, or until its walk starts repeating if it never does.
+pub fn ghost_path(documents: &Documents, start: NodeId) -> Vec<NodeId> {
+    let network = &documents.network;
+    let cycle = Cycle::find(network, &documents.steps, start, |node| {
+        network.name(node).ends_with('Z')
+    });
+    let step_count = cycle
+        .first_hit()
+        .unwrap_or(cycle.tail_length + cycle.cycle_length);
+
+    let mut path = vec![start];
+    path.extend(
+        network
+            .walk(start, &documents.steps)
+            .take(step_count as usize),
+    );
+    path
+}
+
+/// Writes the network as a Graphviz DOT graph, with an edge labeled `L` or
+/// `R` for each way out of a node. Start nodes (`..A`) are green, end nodes
+/// (`..Z`) are red, and if `show_paths` is set, the path of each ghost is
+/// drawn on top in its own color.
+pub fn to_dot(documents: &Documents, show_paths: bool) -> String {
+    let network = &documents.network;
+    let mut lines = vec!["digraph network {".to_owned()];
+
+    for node in network.nodes() {
+        let name = network.name(node);
+        let style = if name.ends_with('A') {
+            " [style=filled, fillcolor=palegreen]"
+        } else if name.ends_with('Z') {
+            " [style=filled, fillcolor=lightcoral]"
+        } else {
+            ""
+        };
+        lines.push(format!("    {}{};", quote(name), style));
+    }
+
+    for node in network.nodes() {
+        let left = network.step(node, Direction::Left);
+        let right = network.step(node, Direction::Right);
+        let edges = if left == right {
+            vec![(left, "L/R")]
+        } else {
+            vec![(left, "L"), (right, "R")]
+        };
+
+        for (next, label) in edges {
+            lines.push(format!(
+                "    {} -> {} [label={}];",
+                quote(network.name(node)),
+                quote(network.name(next)),
+                quote(label)
+            ));
+        }
+    }
+
+    if show_paths {
+        let starts = network
+            .nodes()
+            .filter(|&node| network.name(node).ends_with('A'));
+        for (start, color) in starts.zip(PATH_COLORS.iter().cycle()) {
+            let path = ghost_path(documents, start);
+            let mut edges: Vec<(NodeId, NodeId)> =
+                path.windows(2).map(|pair| (pair[0], pair[1])).collect();
+            edges.sort();
+            edges.dedup();
+
+            for (from, to) in edges {
+                lines.push(format!(
+                    "    {} -> {} [color={}, penwidth=2, constraint=false];",
+                    quote(network.name(from)),
+                    quote(network.name(to)),
+                    color
+                ));
+            }
+        }
+    }
+
+    lines.push("}".to_owned());
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d8::get_documents;
+    use crate::utils;
+
+    #[test]
+    fn export() {
+        let documents = get_documents(&utils::read_input("src/d8/test_input_p2.dat")).unwrap();
+        let dot = to_dot(&documents, false);
+
+        assert!(dot.starts_with("digraph network {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("    \"11A\" [style=filled, fillcolor=palegreen];\n"));
+        assert!(dot.contains("    \"22Z\" [style=filled, fillcolor=lightcoral];\n"));
+        assert!(dot.contains("    \"XXX\";\n"));
+        assert!(dot.contains("    \"11A\" -> \"11B\" [label=\"L\"];\n"));
+        assert!(dot.contains("    \"11A\" -> \"XXX\" [label=\"R\"];\n"));
+        assert!(dot.contains("    \"22B\" -> \"22C\" [label=\"L/R\"];\n"));
+        assert!(!dot.contains("color=blue"));
+    }
+
+    #[test]
+    fn ghost_paths() {
+        let documents = get_documents(&utils::read_input("src/d8/test_input_p2.dat")).unwrap();
+        let network = &documents.network;
+        let path: Vec<&str> = ghost_path(&documents, network.id("22A").unwrap())
+            .into_iter()
+            .map(|node| network.name(node))
+            .collect();
+        assert_eq!(path, ["22A", "22B", "22C", "22Z"]);
+
+        let dot = to_dot(&documents, true);
+        assert!(
+            dot.contains("    \"11B\" -> \"11Z\" [color=blue, penwidth=2, constraint=false];\n")
+        );
+        assert!(dot.contains("    \"22C\" -> \"22Z\" [color=red, penwidth=2, constraint=false];\n"));
+    }
+}
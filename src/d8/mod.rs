@@ -1,135 +1,260 @@
-use std::collections::HashMap;
-use std::time::Instant;
+use crate::error::{ParseError, ParseErrorKind, SolveError};
+use crate::solver::{answer_from_u64, Answer, Solver};
+use crate::utils::math::CrtError;
 
-use crate::utils;
+mod cycle;
+mod dot;
+mod network;
 
-fn get_key_map(lines: &[String]) -> HashMap<String, (String, String)> {
-    let mut map: HashMap<String, (String, String)> = HashMap::new();
+pub use cycle::{first_common_hit, Cycle};
+pub use dot::{ghost_path, to_dot};
+pub use network::{Direction, Network, NodeId, Walk};
+
+/// The left/right instructions and the network of nodes they are followed on.
+#[derive(Debug, Clone)]
+pub struct Documents {
+    pub steps: Vec<Direction>,
+    pub network: Network,
+}
+
+fn get_network(input: &str, lines: &[&str]) -> Result<Network, ParseError> {
+    let mut nodes: Vec<(&str, &str, &str, &str)> = vec![];
 
     for line in lines.iter().skip(2) {
+        let malformed_error = || {
+            ParseError::at(
+                input,
+                line,
+                ParseErrorKind::Expected("`<node> = (<left>, <right>)`"),
+            )
+        };
+
         let (key, value_pair) = match line.split(" = ").collect::<Vec<&str>>()[..] {
             [a, b] => (a, b),
-            _ => panic!("Malformed line: {:?}", line),
+            _ => return Err(malformed_error()),
         };
 
-        let (left, right) = match value_pair[1..(value_pair.len() - 1)]
+        let (left, right) = match value_pair
+            .strip_prefix('(')
+            .and_then(|v| v.strip_suffix(')'))
+            .ok_or_else(malformed_error)?
             .split(", ")
             .collect::<Vec<&str>>()[..]
         {
             [a, b] => (a, b),
-            _ => panic!("Malformed value_pair: {:?}", value_pair),
+            _ => return Err(malformed_error()),
         };
 
-        map.insert(key.to_owned(), (left.to_owned(), right.to_owned()));
+        nodes.push((line, key, left, right));
+    }
+
+    // add every node before connecting them, so that nodes can lead to nodes
+    // defined further down
+    let mut network = Network::new();
+    for (line, key, _, _) in nodes.iter() {
+        if network.id(key).is_some() {
+            return Err(ParseError::at(
+                input,
+                line,
+                ParseErrorKind::Expected("a node that is not defined yet"),
+            ));
+        }
+        network.add_node(key);
+    }
+
+    for (_, key, left, right) in nodes {
+        let find = |reference: &str| {
+            network
+                .id(reference)
+                .ok_or_else(|| ParseError::at(input, reference, ParseErrorKind::Unknown("node")))
+        };
+        let (node, left, right) = (find(key)?, find(left)?, find(right)?);
+        network.connect(node, left, right);
     }
 
-    map
+    Ok(network)
 }
 
-fn step_key(key: &mut String, step: &char, map: &HashMap<String, (String, String)>) {
-    let (left, right) = map
-        .get(key)
-        .unwrap_or_else(|| panic!("Map does not contain key {:?}!", key));
-    match step {
-        'L' => *key = left.to_owned(),
-        'R' => *key = right.to_owned(),
-        _ => panic!("Malformed step: {:?}", step),
+pub fn get_documents(input: &str) -> Result<Documents, ParseError> {
+    let lines: Vec<&str> = input.lines().collect();
+
+    let step_line = lines.first().copied().unwrap_or_default();
+    if step_line.is_empty() {
+        return Err(ParseError::at(
+            input,
+            step_line,
+            ParseErrorKind::Expected("a line of `L` and `R` instructions"),
+        ));
     }
+    let steps = step_line
+        .char_indices()
+        .map(|(i, c)| {
+            Direction::from_char(c).ok_or_else(|| {
+                ParseError::at(
+                    input,
+                    &step_line[i..(i + c.len_utf8())],
+                    ParseErrorKind::Expected("`L` or `R`"),
+                )
+            })
+        })
+        .collect::<Result<Vec<Direction>, ParseError>>()?;
+
+    if let Some(line) = lines.get(1).filter(|line| !line.is_empty()) {
+        return Err(ParseError::at(
+            input,
+            line,
+            ParseErrorKind::Expected("a blank line after the instructions"),
+        ));
+    }
+
+    Ok(Documents {
+        steps,
+        network: get_network(input, &lines)?,
+    })
 }
 
-fn count_steps(filename: &str) -> u64 {
-    let lines: Vec<String> = utils::read_lines(filename).collect();
-    let steps: Vec<char> = lines[0].chars().collect();
+fn missing_node_error(node: &str) -> SolveError {
+    SolveError::Invalid(format!("the network has no node {:?}", node))
+}
 
-    let map = get_key_map(&lines);
+fn find_node(network: &Network, name: &str) -> Result<NodeId, SolveError> {
+    network.id(name).ok_or_else(|| missing_node_error(name))
+}
 
-    let mut key = "AAA".to_owned();
+pub fn count_steps(documents: &Documents) -> Result<u64, SolveError> {
+    let network = &documents.network;
+    let start = find_node(network, "AAA")?;
+    let end = find_node(network, "ZZZ")?;
 
-    let mut step_count = 0;
-    for step in steps.into_iter().cycle() {
-        step_count += 1;
-        step_key(&mut key, &step, &map);
-        if key == "ZZZ" {
-            break;
-        }
-    }
-    step_count
+    // after this many steps, the walk has been in every (node, instruction
+    // index) state it will ever be in
+    let state_count = network.len() * documents.steps.len();
+    let step_count = network
+        .walk(start, &documents.steps)
+        .take(state_count)
+        .position(|node| node == end)
+        .ok_or_else(|| SolveError::Invalid("ZZZ can not be reached from AAA".to_owned()))?
+        + 1;
+    Ok(step_count as u64)
 }
 
-fn get_least_common_multiple(numbers: &[u64]) -> u64 {
-    // recursively solve for each set of two values
-    if numbers.len() == 1 {
-        return numbers[0];
+pub fn count_multi_steps(documents: &Documents) -> Result<u64, SolveError> {
+    let network = &documents.network;
+    let cycles: Vec<Cycle> = network
+        .nodes()
+        .filter(|&node| network.name(node).ends_with('A'))
+        .map(|start| {
+            Cycle::find(network, &documents.steps, start, |node| {
+                network.name(node).ends_with('Z')
+            })
+        })
+        .collect();
+    if cycles.is_empty() {
+        return Err(missing_node_error("..A"));
     }
-    let mut a = numbers[0];
-    let mut b = get_least_common_multiple(&numbers[1..]);
 
-    // Make sure b >= a
-    if a > b {
-        (a, b) = (b, a);
+    first_common_hit(&cycles).map_err(|err| match err {
+        CrtError::NoSolution => {
+            SolveError::Invalid("the ghosts are never all at a node ending in Z at once".to_owned())
+        }
+        CrtError::Overflow => {
+            SolveError::Invalid("the ghosts take too many steps to count".to_owned())
+        }
+    })
+}
+
+pub struct Day8;
+
+impl Solver for Day8 {
+    type Input = Documents;
+
+    const DAY: u32 = 8;
+    const TITLE: &'static str = "Haunted Wasteland";
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        get_documents(input)
     }
 
-    // Keep start values
-    let a_start = a;
-    let b_start = b;
+    fn part_one(&self, documents: &Self::Input) -> Result<Answer, SolveError> {
+        answer_from_u64(count_steps(documents)?)
+    }
 
-    // Search for lcm
-    loop {
-        a += a_start * ((b - a) / a_start);
-        if a == b {
-            return a;
-        }
-        b += b_start;
+    fn part_two(&self, documents: &Self::Input) -> Result<Answer, SolveError> {
+        answer_from_u64(count_multi_steps(documents)?)
     }
 }
 
-fn count_multi_steps(filename: &str) -> u64 {
-    let lines: Vec<String> = utils::read_lines(filename).collect();
-    let steps: Vec<char> = lines[0].chars().collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
 
-    let map = get_key_map(&lines);
-
-    let mut keys: Vec<String> = vec![];
-    for key in map.keys() {
-        if key.as_bytes()[2] == b'A' {
-            keys.push(key.to_owned());
-        }
+    #[test]
+    fn part_one_example() {
+        let documents = get_documents(&utils::read_input("src/d8/test_input_p1.dat")).unwrap();
+        assert_eq!(count_steps(&documents), Ok(6));
     }
 
-    let mut key_cycles: Vec<u64> = vec![];
-    for key in keys.iter_mut() {
-        let mut key_step_count = 0;
-        for step in steps.iter().cycle() {
-            key_step_count += 1;
-            step_key(key, step, &map);
-            if key.as_bytes()[2] == b'Z' {
-                break;
-            }
-        }
-        key_cycles.push(key_step_count);
+    #[test]
+    fn part_two_example() {
+        let documents = get_documents(&utils::read_input("src/d8/test_input_p2.dat")).unwrap();
+        assert_eq!(count_multi_steps(&documents), Ok(6));
     }
 
-    get_least_common_multiple(&key_cycles)
-}
+    #[test]
+    fn undefined_node() {
+        let err = get_documents("L\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::Unknown("node"));
+        assert_eq!(err.text, "BBB");
+    }
 
-fn test() {
-    assert_eq!(count_steps("src/d8/test_input_p1.dat"), 6);
-    assert_eq!(count_multi_steps("src/d8/test_input_p2.dat"), 6);
-}
+    #[test]
+    fn duplicate_node() {
+        let err =
+            get_documents("L\n\nAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\nAAA = (AAA, AAA)").unwrap_err();
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::Expected("a node that is not defined yet")
+        );
+        assert_eq!((err.line, err.column), (5, 1));
+    }
 
-pub fn test_final() {
-    assert_eq!(count_steps("src/d8/full_input.dat"), 16897);
-    assert_eq!(count_multi_steps("src/d8/full_input.dat"), 16563603485021);
-}
+    #[test]
+    fn missing_blank_line() {
+        let err = get_documents("L\nAAA = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)").unwrap_err();
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::Expected("a blank line after the instructions")
+        );
+        assert_eq!(err.line, 2);
+    }
 
-pub fn main() {
-    test();
+    #[test]
+    fn nodes_are_interned() {
+        let documents = get_documents(&utils::read_input("src/d8/test_input_p1.dat")).unwrap();
+        let network = &documents.network;
+        assert_eq!(network.len(), 3);
+        assert_eq!(
+            documents.steps,
+            [Direction::Left, Direction::Left, Direction::Right]
+        );
 
-    let mut now = Instant::now();
-    let sum_p1 = count_steps("src/d8/full_input.dat");
-    println!("Part one result: {} (took {:?})", sum_p1, now.elapsed());
+        let start = network.id("AAA").unwrap();
+        let path: Vec<&str> = network
+            .walk(start, &documents.steps)
+            .take(2)
+            .map(|node| network.name(node))
+            .collect();
+        assert_eq!(path, ["BBB", "AAA"]);
+    }
 
-    now = Instant::now();
-    let sum_p2 = count_multi_steps("src/d8/full_input.dat");
-    println!("Part two result: {} (took {:?})", sum_p2, now.elapsed());
+    #[test]
+    fn unreachable_end() {
+        let documents =
+            get_documents("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)").unwrap();
+        assert!(matches!(
+            count_steps(&documents),
+            Err(SolveError::Invalid(_))
+        ));
+    }
 }
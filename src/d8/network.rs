@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+/// A node of a [`Network`], numbered from 0 in the order the nodes were added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// Reads an `L` or `R` instruction.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'L' => Some(Direction::Left),
+            'R' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Direction::Left => 'L',
+            Direction::Right => 'R',
+        }
+    }
+}
+
+/// A network of named nodes, where each node leads to one node on the left
+/// and one on the right. Names are interned, so walking the network only
+/// deals with [`NodeId`]s.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Network {
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    /// The left and right node of each node.
+    edges: Vec<[NodeId; 2]>,
+}
+
+impl Network {
+    pub fn new() -> Self {
+        Network::default()
+    }
+
+    /// Adds a node, if there is none with this name. New nodes lead back to
+    /// themselves until they are connected.
+    pub fn add_node(&mut self, name: &str) -> NodeId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = NodeId(self.names.len());
+        self.names.push(name.to_owned());
+        self.ids.insert(name.to_owned(), id);
+        self.edges.push([id, id]);
+        id
+    }
+
+    pub fn connect(&mut self, node: NodeId, left: NodeId, right: NodeId) {
+        self.edges[node.0] = [left, right];
+    }
+
+    /// Finds a node by name.
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, node: NodeId) -> &str {
+        &self.names[node.0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Every node, in the order they were added.
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.names.len()).map(NodeId)
+    }
+
+    pub fn step(&self, node: NodeId, direction: Direction) -> NodeId {
+        match direction {
+            Direction::Left => self.edges[node.0][0],
+            Direction::Right => self.edges[node.0][1],
+        }
+    }
+
+    /// Follows `instructions` from `start`, repeating them forever.
+    pub fn walk<'a>(&'a self, start: NodeId, instructions: &'a [Direction]) -> Walk<'a> {
+        Walk {
+            network: self,
+            instructions,
+            node: start,
+            index: 0,
+        }
+    }
+}
+
+/// An endless walk through a [`Network`], yielding the node reached after
+/// each step. Made by [`Network::walk`].
+#[derive(Debug, Clone)]
+pub struct Walk<'a> {
+    network: &'a Network,
+    instructions: &'a [Direction],
+    node: NodeId,
+    /// The index of the next instruction.
+    index: usize,
+}
+
+impl Walk<'_> {
+    /// The current node, and the index of the instruction that will be
+    /// followed next.
+    pub fn state(&self) -> (NodeId, usize) {
+        (self.node, self.index)
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let direction = *self.instructions.get(self.index)?;
+        self.node = self.network.step(self.node, direction);
+        self.index = (self.index + 1) % self.instructions.len();
+        Some(self.node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interning() {
+        let mut network = Network::new();
+        let a = network.add_node("AAA");
+        let b = network.add_node("BBB");
+        assert_eq!(network.add_node("AAA"), a);
+        assert_eq!(network.id("BBB"), Some(b));
+        assert_eq!(network.id("CCC"), None);
+        assert_eq!(network.name(b), "BBB");
+        assert_eq!(network.nodes().collect::<Vec<_>>(), [a, b]);
+    }
+
+    #[test]
+    fn walk() {
+        let mut network = Network::new();
+        let [a, b, c] = ["A", "B", "C"].map(|name| network.add_node(name));
+        network.connect(a, b, c);
+        network.connect(b, a, c);
+
+        let instructions = [Direction::Left, Direction::Left, Direction::Right];
+        let mut walk = network.walk(a, &instructions);
+        assert_eq!(walk.by_ref().take(4).collect::<Vec<_>>(), [b, a, c, c]);
+        assert_eq!(walk.state(), (c, 1));
+        assert_eq!(network.walk(a, &[]).next(), None);
+    }
+}
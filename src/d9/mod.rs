@@ -1,7 +1,8 @@
-use std::time::Instant;
-
+use crate::error::{ParseError, SolveError};
+use crate::solver::{Answer, Solver};
 use crate::utils;
 
+#[allow(dead_code)]
 fn plot_layers(layers: &[Vec<i32>]) {
     let depth = layers.len();
     for (i, layer) in layers.iter().enumerate() {
@@ -18,11 +19,28 @@ fn plot_layers(layers: &[Vec<i32>]) {
     println!();
 }
 
-fn get_next_sum(filename: &str) -> i32 {
+pub fn get_histories(input: &str) -> Result<Vec<Vec<i32>>, ParseError> {
+    input
+        .lines()
+        .map(|line| utils::string_to_array(line, " ", 0).map_err(|err| err.within(input, line)))
+        .collect()
+}
+
+pub fn get_next_sum(histories: &[Vec<i32>]) -> Result<i32, SolveError> {
     let mut next_sum: i32 = 0;
 
-    for line in utils::read_lines(filename) {
-        let mut numbers = utils::string_to_array::<i32>(&line, " ", 0);
+    for (history_i, history) in histories.iter().enumerate() {
+        let exhausted_error = || {
+            SolveError::Invalid(format!(
+                "history {} never reaches a constant difference",
+                history_i + 1
+            ))
+        };
+        if history.len() < 2 {
+            return Err(exhausted_error());
+        }
+
+        let mut numbers = history.clone();
         numbers.reverse();
 
         let mut layers: Vec<Vec<i32>> = vec![numbers];
@@ -36,6 +54,9 @@ fn get_next_sum(filename: &str) -> i32 {
                 break;
             } else {
                 depth += 1;
+                if depth + 1 >= history.len() {
+                    return Err(exhausted_error());
+                }
                 layers.push(vec![next_difference]);
 
                 for i in 0..depth {
@@ -46,27 +67,33 @@ fn get_next_sum(filename: &str) -> i32 {
         }
     }
 
-    next_sum
+    Ok(next_sum)
 }
 
-fn test() {
-    assert_eq!(get_next_sum("src/d9/test_input.dat"), 114);
-    // assert_eq!(get_next_sum("src/d9/test_input.dat"), 5905);
-}
+pub struct Day9;
 
-pub fn test_final() {
-    // assert_eq!(get_next_sum("src/d9/full_input.dat"), 251136060);
-    // assert_eq!(get_next_sum("src/d9/full_input.dat"), 249400220);
-}
+impl Solver for Day9 {
+    type Input = Vec<Vec<i32>>;
 
-pub fn main() {
-    test();
+    const DAY: u32 = 9;
+    const TITLE: &'static str = "Mirage Maintenance";
 
-    let mut now = Instant::now();
-    let sum_p1 = get_next_sum("src/d9/full_input.dat");
-    println!("Part one result: {} (took {:?})", sum_p1, now.elapsed());
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError> {
+        get_histories(input)
+    }
 
-    // now = Instant::now();
-    // let sum_p2 = get_next_sum("src/d9/full_input.dat");
-    // println!("Part two result: {} (took {:?})", sum_p2, now.elapsed());
+    fn part_one(&self, histories: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(get_next_sum(histories)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_one_example() {
+        let histories = get_histories(&utils::read_input("src/d9/test_input.dat")).unwrap();
+        assert_eq!(get_next_sum(&histories), Ok(114));
+    }
 }
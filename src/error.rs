@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fmt;
+
+/// What went wrong while parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The text could not be parsed as a number of the expected type.
+    InvalidNumber,
+    /// The text did not have the expected format, described by the string.
+    Expected(&'static str),
+    /// The text refers to something that does not exist, such as a card or node.
+    Unknown(&'static str),
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::InvalidNumber => write!(f, "invalid number"),
+            ParseErrorKind::Expected(expected) => write!(f, "expected {}", expected),
+            ParseErrorKind::Unknown(thing) => write!(f, "unknown {}", thing),
+        }
+    }
+}
+
+/// An error in a puzzle input, pointing at the offending text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line of the offending text, starting at 1.
+    pub line: usize,
+    /// The column (in chars) of the offending text, starting at 1.
+    pub column: usize,
+    pub text: String,
+    pub kind: ParseErrorKind,
+}
+
+/// Gets the byte offset of `inner` in `outer`, if `inner` is a slice of `outer`.
+fn offset_in(outer: &str, inner: &str) -> Option<usize> {
+    let offset = (inner.as_ptr() as usize).checked_sub(outer.as_ptr() as usize)?;
+    (offset + inner.len() <= outer.len()).then_some(offset)
+}
+
+impl ParseError {
+    /// Creates an error pointing at `text`, which should be a slice of `input`.
+    /// If it is not, the error points at the start of `input`.
+    pub fn at(input: &str, text: &str, kind: ParseErrorKind) -> Self {
+        let offset = offset_in(input, text).unwrap_or(0);
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: text.to_owned(),
+            kind,
+        }
+    }
+
+    /// Moves an error found in `inner` to its position in `outer`, where
+    /// `inner` is a slice of `outer`.
+    pub fn within(self, outer: &str, inner: &str) -> Self {
+        let start = ParseError::at(outer, inner, self.kind.clone());
+
+        ParseError {
+            line: start.line + self.line - 1,
+            column: if self.line == 1 {
+                start.column + self.column - 1
+            } else {
+                self.column
+            },
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}, found {:?}",
+            self.line, self.column, self.kind, self.text
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// An error that stopped a puzzle from being solved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Parse(ParseError),
+    /// The input was parsed, but it does not describe a puzzle with an answer.
+    Invalid(String),
+    /// The part has not been solved yet.
+    NotImplemented,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Parse(err) => write!(f, "parse error at {}", err),
+            SolveError::Invalid(reason) => write!(f, "invalid input: {}", reason),
+            SolveError::NotImplemented => write!(f, "not implemented"),
+        }
+    }
+}
+
+impl Error for SolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SolveError::Parse(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(err: ParseError) -> Self {
+        SolveError::Parse(err)
+    }
+}
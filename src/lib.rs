@@ -0,0 +1,41 @@
+//! Solutions to Advent of Code 2023.
+//!
+//! Every day lives in its own module and implements [`Solver`]. The
+//! registry in [`DAYS`] holds all of them as type-erased [`Puzzle`]s.
+
+pub mod bench;
+pub mod d1;
+pub mod d2;
+pub mod d3;
+pub mod d4;
+pub mod d5;
+pub mod d6;
+pub mod d7;
+pub mod d8;
+pub mod d9;
+pub mod error;
+pub mod manifest;
+pub mod pool;
+pub mod solver;
+pub mod utils;
+
+pub use error::{ParseError, ParseErrorKind, SolveError};
+pub use solver::{answer_from_u64, run_parts, Answer, ParsedInput, Part, PartRun, Puzzle, Solver};
+
+/// Every solved day, in calendar order.
+pub static DAYS: [&dyn Puzzle; 9] = [
+    &d1::Day1,
+    &d2::Day2,
+    &d3::Day3,
+    &d4::Day4,
+    &d5::Day5,
+    &d6::Day6,
+    &d7::Day7,
+    &d8::Day8,
+    &d9::Day9,
+];
+
+/// Finds the puzzle for a given day in [`DAYS`].
+pub fn find_day(day: u32) -> Option<&'static dyn Puzzle> {
+    DAYS.iter().find(|puzzle| puzzle.day() == day).copied()
+}
@@ -1,33 +1,506 @@
-use std::env;
-
-mod utils;
-
-macro_rules! create_main {
-    ($($number: literal: $file: ident),*) => {
-        $(mod $file;)*
-
-        fn main() {
-            let args: Vec<String> = env::args().collect();
-            if args.len() == 1 {
-                $(
-                    println!("Day {}", $number);
-                    $file::test_final();
-                    $file::main();
-                )*
-            } else if args.len() == 2 {
-                if let Ok(day) = args[1].parse() {
-                    match day {
-                        $($number => $file::main(),)*
-                        other => panic!("Unknown day: {}", other),
-                    }
-                } else {
-                    panic!("Could not parse day as int: {}", args[1]);
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, io, process};
+
+use aoc2023::bench::{self, BenchOptions, DayBench, Stats};
+use aoc2023::d7::{self, Ruleset};
+use aoc2023::d8;
+use aoc2023::manifest::{Manifest, Status, Verification, DEFAULT_MANIFEST_PATH};
+use aoc2023::{find_day, pool, run_parts, ParsedInput, Part, PartRun, Puzzle, SolveError, DAYS};
+use cli::{Command, Format, InputSource};
+use report::{Record, RecordStatus};
+
+mod cli;
+mod report;
+
+fn read_input(puzzle: &dyn Puzzle, source: &InputSource) -> io::Result<String> {
+    match source {
+        InputSource::Default => fs::read_to_string(puzzle.input_path()),
+        InputSource::File(path) => fs::read_to_string(path),
+        InputSource::Stdin => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+/// Loads the default manifest, used to check the answers of `run`. A missing
+/// manifest is not an error, since the answers are then simply unverified.
+fn load_default_manifest() -> Option<Manifest> {
+    if !Path::new(DEFAULT_MANIFEST_PATH).exists() {
+        return None;
+    }
+    Manifest::load(DEFAULT_MANIFEST_PATH)
+        .map_err(|err| eprintln!("Warning: {}", err))
+        .ok()
+}
+
+/// The path of an input as it is written in the manifest, or `-` for stdin.
+fn input_name(puzzle: &dyn Puzzle, source: &InputSource) -> PathBuf {
+    match source {
+        InputSource::Default => puzzle.input_path(),
+        InputSource::File(path) => path.clone(),
+        InputSource::Stdin => PathBuf::from("-"),
+    }
+}
+
+/// Runs the given parts of a puzzle on `input`, returning one record per part.
+fn run(
+    puzzle: &dyn Puzzle,
+    input: &str,
+    input_path: &Path,
+    parts: &[Part],
+    manifest: Option<&Manifest>,
+) -> Vec<Record> {
+    to_records(
+        puzzle,
+        input_path,
+        &run_parts(puzzle, input, parts),
+        manifest,
+    )
+}
+
+/// Makes a record of each run of a puzzle's parts, checking the answers
+/// against `manifest`.
+fn to_records(
+    puzzle: &dyn Puzzle,
+    input_path: &Path,
+    runs: &[PartRun],
+    manifest: Option<&Manifest>,
+) -> Vec<Record> {
+    runs.iter()
+        .map(|run| {
+            let expected = manifest
+                .and_then(|m| m.find(puzzle.day(), run.part, input_path))
+                .map(|expected| expected.answer);
+            Record::from_run(
+                puzzle.day(),
+                input_path.display().to_string(),
+                run,
+                expected,
+            )
+        })
+        .collect()
+}
+
+/// Makes an error record for each part that could not be run because its
+/// input could not be read, like `verify` does for a missing input.
+fn read_error_records(
+    puzzle: &dyn Puzzle,
+    input_path: &Path,
+    parts: &[Part],
+    err: &io::Error,
+) -> Vec<Record> {
+    parts
+        .iter()
+        .map(|&part| {
+            Record::from_error(
+                puzzle.day(),
+                part,
+                input_path.display().to_string(),
+                err.to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Prints the result of running one part. Errors are printed to stderr.
+fn print_record(record: &Record) {
+    let mut elapsed = Duration::from_nanos(record.solve_ns.unwrap_or(0));
+    if record.part == Part::One {
+        elapsed += Duration::from_nanos(record.parse_ns.unwrap_or(0));
+    }
+
+    match (record.status, record.answer) {
+        (RecordStatus::NotSolved, _) => println!("Part {} is not solved", record.part),
+        (RecordStatus::Error, _) => eprintln!(
+            "Part {} failed: {}",
+            record.part,
+            record.error.as_deref().unwrap_or_default()
+        ),
+        (RecordStatus::Fail, Some(answer)) => println!(
+            "Part {} result: {} (took {:?}), but expected {}",
+            record.part,
+            answer,
+            elapsed,
+            record.expected.unwrap_or_default()
+        ),
+        (_, answer) => println!(
+            "Part {} result: {} (took {:?})",
+            record.part,
+            answer.unwrap_or_default(),
+            elapsed
+        ),
+    }
+}
+
+/// Prints records in the given format. Returns false if any of them failed.
+fn print_records(records: &[Record], format: Format) -> bool {
+    for record in records {
+        match format {
+            Format::Text => print_record(record),
+            Format::Json => println!("{}", record.to_json()),
+        }
+    }
+    !records.iter().any(Record::is_failure)
+}
+
+/// Runs both parts of every day on a pool of `jobs` threads. Each day is
+/// parsed once as a job of its own, and then each of its parts is solved as
+/// a separate job. The results are printed in day order once all jobs are
+/// done. Returns false if any day could not be run, or any part failed.
+fn run_all(jobs: usize, format: Format) -> bool {
+    let manifest = load_default_manifest();
+    let inputs: Vec<(PathBuf, io::Result<String>)> = DAYS
+        .iter()
+        .map(|&puzzle| {
+            (
+                input_name(puzzle, &InputSource::Default),
+                read_input(puzzle, &InputSource::Default),
+            )
+        })
+        .collect();
+
+    let days: Vec<usize> = (0..DAYS.len()).collect();
+    let parsed: Vec<Option<ParsedInput>> = pool::map(&days, jobs, |&i| {
+        let input = inputs[i].1.as_ref().ok()?;
+        Some(ParsedInput::new(DAYS[i], input))
+    });
+
+    let part_jobs: Vec<(usize, Part)> = days
+        .iter()
+        .filter(|&&i| parsed[i].is_some())
+        .flat_map(|&i| Part::BOTH.map(|part| (i, part)))
+        .collect();
+    let part_runs = pool::map(&part_jobs, jobs, |&(i, part)| {
+        let parsed = parsed[i].as_ref().expect("only parsed days have part jobs");
+        parsed.solve(DAYS[i], part)
+    });
+
+    let mut runs: Vec<Vec<PartRun>> = vec![vec![]; DAYS.len()];
+    for (&(i, _), run) in part_jobs.iter().zip(part_runs) {
+        runs[i].push(run);
+    }
+    let records: Vec<Vec<Record>> = days
+        .iter()
+        .map(|&i| match &inputs[i] {
+            (input_path, Ok(_)) => to_records(DAYS[i], input_path, &runs[i], manifest.as_ref()),
+            (input_path, Err(err)) => read_error_records(DAYS[i], input_path, &Part::BOTH, err),
+        })
+        .collect();
+
+    let mut success = true;
+    for ((puzzle, (input_path, input)), day_records) in
+        DAYS.iter().zip(inputs.iter()).zip(records.iter())
+    {
+        if format == Format::Text {
+            println!("Day {}: {}", puzzle.day(), puzzle.title());
+            if let Err(err) = input {
+                eprintln!("Could not read {:?}: {}", input_path, err);
+                success = false;
+                continue;
+            }
+        }
+        success &= print_records(day_records, format);
+    }
+
+    success
+}
+
+/// Prints a table of Camel Cards hands, from weakest to strongest.
+fn print_ranked_hands(ranked: &[d7::RankedHand]) {
+    let hand_width = ranked
+        .iter()
+        .map(|r| r.hand.hand_string().chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Hand".len());
+    let type_width = ranked
+        .iter()
+        .map(|r| r.hand.hand_type().name().len())
+        .max()
+        .unwrap_or(0)
+        .max("Type".len());
+
+    println!(
+        "{:>4}  {:<hand_width$}  {:<type_width$}  {:>7}  {:>6}  {:>8}",
+        "Rank", "Hand", "Type", "Wild as", "Bet", "Winnings"
+    );
+    for r in ranked {
+        let substitution = match r.hand.substitution() {
+            Some(card) => card.to_string(),
+            None => "-".to_owned(),
+        };
+        println!(
+            "{:>4}  {:<hand_width$}  {:<type_width$}  {:>7}  {:>6}  {:>8}",
+            r.rank,
+            r.hand.hand_string(),
+            r.hand.hand_type().name(),
+            substitution,
+            r.hand.bet(),
+            r.winnings
+        );
+    }
+    println!();
+}
+
+/// Prints a table of verification results. Returns false if any failed.
+fn print_verifications(verifications: &[Verification]) -> bool {
+    let input_width = verifications
+        .iter()
+        .map(|v| v.expected.input.display().to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("Input".len());
+
+    println!(
+        "{:>3}  {:>4}  {:<input_width$}  {:>16}  {:>12}  Status",
+        "Day", "Part", "Input", "Expected", "Time"
+    );
+    for verification in verifications {
+        let expected = &verification.expected;
+        let time = match (verification.parse_duration, verification.solve_duration) {
+            (None, None) => String::new(),
+            (parse, solve) => format!(
+                "{:.2?}",
+                parse.unwrap_or_default() + solve.unwrap_or_default()
+            ),
+        };
+        let status = match &verification.status {
+            Status::Pass => "pass".to_owned(),
+            Status::Fail(answer) => format!("FAIL (got {})", answer),
+            Status::Unverified(answer) => format!("got {}", answer),
+            Status::NotSolved => "not solved".to_owned(),
+            Status::Error(err) => format!("ERROR ({})", err),
+        };
+
+        println!(
+            "{:>3}  {:>4}  {:<input_width$}  {:>16}  {:>12}  {}",
+            expected.day,
+            expected.part.number(),
+            expected.input.display(),
+            expected.answer,
+            time,
+            status
+        );
+    }
+
+    let failures = verifications
+        .iter()
+        .filter(|v| v.status.is_failure())
+        .count();
+    println!(
+        "\n{} passed, {} failed, {} not solved",
+        verifications
+            .iter()
+            .filter(|v| v.status == Status::Pass)
+            .count(),
+        failures,
+        verifications
+            .iter()
+            .filter(|v| v.status == Status::NotSolved)
+            .count(),
+    );
+
+    failures == 0
+}
+
+fn print_stats_row(name: &str, stats: &Stats) {
+    println!(
+        "{:<8}  {:>10.2?}  {:>10.2?}  {:>10.2?}  {:>10.2?}",
+        name, stats.min, stats.median, stats.mean, stats.stddev
+    );
+}
+
+/// Benchmarks the given days, printing a table for each day and a summary
+/// of the median times. Returns false if any day could not be benchmarked.
+fn run_bench(puzzles: &[&dyn Puzzle], options: &BenchOptions) -> bool {
+    let mut success = true;
+    let mut results: Vec<DayBench> = vec![];
+
+    for &puzzle in puzzles {
+        println!(
+            "Day {}: {} ({} runs, {} warm-up)",
+            puzzle.day(),
+            puzzle.title(),
+            options.runs,
+            options.warmup
+        );
+
+        let result = read_input(puzzle, &InputSource::Default)
+            .map_err(|err| format!("Could not read {:?}: {}", puzzle.input_path(), err))
+            .and_then(|input| {
+                bench::bench_puzzle(puzzle, &input, options)
+                    .map_err(|err| format!("Could not parse input: {}", err))
+            });
+        let day_bench = match result {
+            Ok(day_bench) => day_bench,
+            Err(err) => {
+                eprintln!("{}\n", err);
+                success = false;
+                continue;
+            }
+        };
+
+        println!(
+            "{:<8}  {:>10}  {:>10}  {:>10}  {:>10}",
+            "Step", "Min", "Median", "Mean", "Stddev"
+        );
+        print_stats_row("parse", &day_bench.parse);
+        for (part, stats) in day_bench.parts.iter() {
+            let name = format!("part {}", part.number());
+            match stats {
+                Ok(stats) => print_stats_row(&name, stats),
+                Err(SolveError::NotImplemented) => println!("{:<8}  not solved", name),
+                Err(err) => {
+                    println!("{:<8}  failed: {}", name, err);
+                    success = false;
                 }
-            } else {
-                panic!("Got too many command line arguments!");
             }
         }
-    };
+        println!();
+
+        results.push(day_bench);
+    }
+
+    println!("Summary (median times)");
+    println!(
+        "{:>5}  {:>10}  {:>10}  {:>10}  {:>10}",
+        "Day", "Parse", "Part 1", "Part 2", "Total"
+    );
+    let mut grand_total = Duration::ZERO;
+    for day_bench in results.iter() {
+        let mut total = day_bench.parse.median;
+        let part_times: Vec<String> = day_bench
+            .parts
+            .iter()
+            .map(|(_, stats)| match stats {
+                Ok(stats) => {
+                    total += stats.median;
+                    format!("{:.2?}", stats.median)
+                }
+                Err(_) => "-".to_owned(),
+            })
+            .collect();
+        grand_total += total;
+
+        println!(
+            "{:>5}  {:>10.2?}  {:>10}  {:>10}  {:>10.2?}",
+            day_bench.day, day_bench.parse.median, part_times[0], part_times[1], total
+        );
+    }
+    println!(
+        "{:>5}  {:>10}  {:>10}  {:>10}  {:>10.2?}",
+        "Total", "", "", "", grand_total
+    );
+
+    success
+}
+
+fn fail(message: &str, code: i32) -> ! {
+    eprintln!("{}", message);
+    process::exit(code);
 }
 
-create_main!(1: d1, 2: d2, 3: d3, 4: d4, 5: d5, 6: d6, 7: d7, 8: d8, 9: d9);
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, format) =
+        cli::parse_args(&args).unwrap_or_else(|err| fail(&format!("{}\n\n{}", err, cli::USAGE), 2));
+
+    match command {
+        Command::RunAll { jobs } => {
+            if !run_all(jobs, format) {
+                process::exit(1);
+            }
+        }
+        Command::Run { day, part, input } => {
+            let puzzle = find_day(day).unwrap_or_else(|| fail(&format!("Unknown day: {}", day), 2));
+            let input_path = input_name(puzzle, &input);
+            let parts = match part {
+                Some(part) => vec![part],
+                None => Part::BOTH.to_vec(),
+            };
+            let records = match (read_input(puzzle, &input), format) {
+                (Ok(input), _) => {
+                    let manifest = load_default_manifest();
+                    run(puzzle, &input, &input_path, &parts, manifest.as_ref())
+                }
+                (Err(err), Format::Json) => read_error_records(puzzle, &input_path, &parts, &err),
+                (Err(err), Format::Text) => fail(&format!("Could not read input: {}", err), 1),
+            };
+            if !print_records(&records, format) {
+                process::exit(1);
+            }
+        }
+        Command::Verify { manifest, jobs } => {
+            let manifest = Manifest::load(manifest).unwrap_or_else(|err| fail(&err, 1));
+            let verifications = manifest.verify(jobs);
+            let success = match format {
+                Format::Text => print_verifications(&verifications),
+                Format::Json => {
+                    let records: Vec<Record> = verifications
+                        .iter()
+                        .map(Record::from_verification)
+                        .collect();
+                    print_records(&records, format)
+                }
+            };
+            if !success {
+                process::exit(1);
+            }
+        }
+        Command::Bench { days, options } => {
+            let puzzles: Vec<&dyn Puzzle> = if days.is_empty() {
+                DAYS.to_vec()
+            } else {
+                days.iter()
+                    .map(|&day| {
+                        find_day(day).unwrap_or_else(|| fail(&format!("Unknown day: {}", day), 2))
+                    })
+                    .collect()
+            };
+            if !run_bench(&puzzles, &options) {
+                process::exit(1);
+            }
+        }
+        Command::Cards {
+            rules,
+            input,
+            table,
+        } => {
+            let ruleset = match rules {
+                Some(path) => Ruleset::load(path).unwrap_or_else(|err| fail(&err, 1)),
+                None => Ruleset::standard(),
+            };
+            let input = read_input(&d7::Day7, &input)
+                .unwrap_or_else(|err| fail(&format!("Could not read input: {}", err), 1));
+            let ranked = d7::rank_hands(&input, &ruleset)
+                .unwrap_or_else(|err| fail(&format!("Could not parse input: {}", err), 1));
+            if table {
+                print_ranked_hands(&ranked);
+            }
+            let winnings: u64 = ranked.iter().map(|ranked| ranked.winnings).sum();
+            println!("Total winnings: {}", winnings);
+        }
+        Command::Network { dot, paths, input } => {
+            let input = read_input(&d8::Day8, &input)
+                .unwrap_or_else(|err| fail(&format!("Could not read input: {}", err), 1));
+            let documents = d8::get_documents(&input)
+                .unwrap_or_else(|err| fail(&format!("Could not parse input: {}", err), 1));
+            let graph = d8::to_dot(&documents, paths);
+            match dot {
+                Some(path) => fs::write(&path, graph)
+                    .unwrap_or_else(|err| fail(&format!("Could not write {:?}: {}", path, err), 1)),
+                None => print!("{}", graph),
+            }
+        }
+        Command::List => {
+            for puzzle in DAYS {
+                println!("{:>2}  {}", puzzle.day(), puzzle.title());
+            }
+        }
+        Command::Help => println!("{}", cli::USAGE),
+    }
+}
@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::error::SolveError;
+use crate::find_day;
+use crate::pool;
+use crate::solver::{run_parts, Answer, Part};
+
+/// The default location of the answers manifest.
+pub const DEFAULT_MANIFEST_PATH: &str = "answers.toml";
+
+/// The known answer to one part of a day, for one input.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ExpectedAnswer {
+    pub day: u32,
+    pub part: Part,
+    pub input: PathBuf,
+    pub answer: Answer,
+}
+
+/// A list of known answers, loaded from a TOML file with one `[[answer]]`
+/// table per answer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Manifest {
+    #[serde(rename = "answer", default)]
+    pub answers: Vec<ExpectedAnswer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    /// The solver gave a different answer.
+    Fail(Answer),
+    /// The solver gave an answer, but there is no known answer to compare
+    /// it with.
+    Unverified(Answer),
+    /// The part has not been solved yet.
+    NotSolved,
+    /// The solver could not be run, or returned an error.
+    Error(String),
+}
+
+impl Status {
+    /// Compares the result of a solver with the known answer, if there is one.
+    pub fn of(result: &Result<Answer, SolveError>, expected: Option<Answer>) -> Self {
+        match (result, expected) {
+            (Ok(answer), Some(expected)) if *answer == expected => Status::Pass,
+            (Ok(answer), Some(_)) => Status::Fail(*answer),
+            (Ok(answer), None) => Status::Unverified(*answer),
+            (Err(SolveError::NotImplemented), _) => Status::NotSolved,
+            (Err(err), _) => Status::Error(err.to_string()),
+        }
+    }
+
+    /// Whether this status should count as a failed verification.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Status::Fail(_) | Status::Error(_))
+    }
+}
+
+/// The result of checking a solver against an [`ExpectedAnswer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    pub expected: ExpectedAnswer,
+    pub status: Status,
+    /// The time spent parsing the input, if it was parsed.
+    pub parse_duration: Option<Duration>,
+    /// The time spent solving the part, if it was solved.
+    pub solve_duration: Option<Duration>,
+}
+
+impl Manifest {
+    pub fn from_toml(string: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(string)
+    }
+
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Self, String> {
+        let path = path.as_ref();
+        let string = fs::read_to_string(path)
+            .map_err(|err| format!("Could not read {:?}: {}", path, err))?;
+        Manifest::from_toml(&string).map_err(|err| format!("Could not parse {:?}: {}", path, err))
+    }
+
+    /// Finds the known answer for a part of a day on the given input.
+    pub fn find(&self, day: u32, part: Part, input: &Path) -> Option<&ExpectedAnswer> {
+        self.answers
+            .iter()
+            .find(|a| a.day == day && a.part == part && a.input == input)
+    }
+
+    /// Runs the solver for every answer in the manifest on a pool of
+    /// `workers` threads, and compares the results. The verifications are
+    /// returned in manifest order.
+    pub fn verify(&self, workers: usize) -> Vec<Verification> {
+        pool::map(&self.answers, workers, verify_answer)
+    }
+}
+
+fn verify_answer(expected: &ExpectedAnswer) -> Verification {
+    let mut verification = Verification {
+        expected: expected.clone(),
+        status: Status::NotSolved,
+        parse_duration: None,
+        solve_duration: None,
+    };
+
+    let Some(puzzle) = find_day(expected.day) else {
+        verification.status = Status::Error(format!("unknown day {}", expected.day));
+        return verification;
+    };
+    let input = match fs::read_to_string(&expected.input) {
+        Ok(input) => input,
+        Err(err) => {
+            verification.status = Status::Error(err.to_string());
+            return verification;
+        }
+    };
+
+    let run = run_parts(puzzle, &input, &[expected.part]).remove(0);
+    verification.parse_duration = Some(run.parse_duration);
+    verification.solve_duration = run.solve_duration;
+    verification.status = Status::of(&run.result, Some(expected.answer));
+    verification
+}
@@ -0,0 +1,77 @@
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// The number of workers to use when none is given: one per available core.
+pub fn default_workers() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Applies `f` to every item on a pool of `workers` threads, returning the
+/// results in the same order as `items`. Each worker takes the next unclaimed
+/// item until there are none left, so slow items do not hold up the rest.
+///
+/// With a single worker, the items are processed on the current thread.
+pub fn map<T, R, F>(items: &[T], workers: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = workers.clamp(1, items.len().max(1));
+    if workers == 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = vec![];
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break done;
+                        };
+                        done.push((index, f(item)));
+                    }
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn results_are_in_input_order() {
+        // earlier items take longer, so they finish after the later ones
+        let items: Vec<u64> = (0..32).collect();
+        let results = map(&items, 4, |&item| {
+            thread::sleep(Duration::from_millis((32 - item) / 4));
+            item * 10
+        });
+        assert_eq!(
+            results,
+            items.iter().map(|item| item * 10).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn more_workers_than_items() {
+        assert_eq!(map(&[1, 2, 3], 16, |item| item + 1), [2, 3, 4]);
+        assert_eq!(map(&[] as &[u8], 4, |item| *item), []);
+    }
+}
@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+use aoc2023::manifest::{Status, Verification};
+use aoc2023::{Answer, Part, PartRun};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordStatus {
+    Pass,
+    Fail,
+    /// The part was solved, but there is no known answer to compare with.
+    Unverified,
+    NotSolved,
+    Error,
+}
+
+/// The outcome of running one part of one day, printed either as text or as
+/// a line of JSON.
+#[derive(Debug, Clone, Serialize)]
+pub struct Record {
+    pub day: u32,
+    pub part: Part,
+    pub input: String,
+    pub answer: Option<Answer>,
+    pub expected: Option<Answer>,
+    pub status: RecordStatus,
+    pub parse_ns: Option<u64>,
+    pub solve_ns: Option<u64>,
+    pub error: Option<String>,
+}
+
+fn to_nanos(duration: Option<Duration>) -> Option<u64> {
+    duration.map(|duration| duration.as_nanos() as u64)
+}
+
+impl From<&Status> for RecordStatus {
+    fn from(status: &Status) -> Self {
+        match status {
+            Status::Pass => RecordStatus::Pass,
+            Status::Fail(_) => RecordStatus::Fail,
+            Status::Unverified(_) => RecordStatus::Unverified,
+            Status::NotSolved => RecordStatus::NotSolved,
+            Status::Error(_) => RecordStatus::Error,
+        }
+    }
+}
+
+impl Record {
+    fn new(day: u32, part: Part, input: String, status: &Status, expected: Option<Answer>) -> Self {
+        let answer = match status {
+            Status::Pass => expected,
+            Status::Fail(answer) | Status::Unverified(answer) => Some(*answer),
+            Status::NotSolved | Status::Error(_) => None,
+        };
+        let error = match status {
+            Status::Error(err) => Some(err.clone()),
+            _ => None,
+        };
+
+        Record {
+            day,
+            part,
+            input,
+            answer,
+            expected,
+            status: RecordStatus::from(status),
+            parse_ns: None,
+            solve_ns: None,
+            error,
+        }
+    }
+
+    /// Makes a record of running a part, comparing the answer with `expected`
+    /// if it is known.
+    pub fn from_run(day: u32, input: String, run: &PartRun, expected: Option<Answer>) -> Self {
+        let status = Status::of(&run.result, expected);
+        let mut record = Record::new(day, run.part, input, &status, expected);
+        record.parse_ns = to_nanos(Some(run.parse_duration));
+        record.solve_ns = to_nanos(run.solve_duration);
+        record
+    }
+
+    /// Makes an error record for a part that could not be run.
+    pub fn from_error(day: u32, part: Part, input: String, err: String) -> Self {
+        Record::new(day, part, input, &Status::Error(err), None)
+    }
+
+    pub fn from_verification(verification: &Verification) -> Self {
+        let expected = &verification.expected;
+        let mut record = Record::new(
+            expected.day,
+            expected.part,
+            expected.input.display().to_string(),
+            &verification.status,
+            Some(expected.answer),
+        );
+        record.parse_ns = to_nanos(verification.parse_duration);
+        record.solve_ns = to_nanos(verification.solve_duration);
+        record
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(self.status, RecordStatus::Fail | RecordStatus::Error)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a record can always be serialized")
+    }
+}
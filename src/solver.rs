@@ -0,0 +1,263 @@
+use std::any::Any;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ParseError, SolveError};
+
+/// The answer to one part of a puzzle.
+pub type Answer = i64;
+
+/// Converts an unsigned answer to an [`Answer`], or errors if it is too large
+/// to fit.
+pub fn answer_from_u64(answer: u64) -> Result<Answer, SolveError> {
+    Answer::try_from(answer)
+        .map_err(|_| SolveError::Invalid(format!("the answer {} is too large", answer)))
+}
+
+/// One of the two parts of a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const BOTH: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+
+    /// Parses a part number, `1` or `2`.
+    pub fn from_number(number: &str) -> Option<Self> {
+        match number {
+            "1" => Some(Part::One),
+            "2" => Some(Part::Two),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<u8> for Part {
+    type Error = String;
+
+    fn try_from(number: u8) -> Result<Self, Self::Error> {
+        match number {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            _ => Err(format!("part must be 1 or 2, got {}", number)),
+        }
+    }
+}
+
+impl From<Part> for u8 {
+    fn from(part: Part) -> Self {
+        part.number()
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "one"),
+            Part::Two => write!(f, "two"),
+        }
+    }
+}
+
+/// A single day's puzzle, split into a parsing step shared by both parts and
+/// one solving step per part.
+pub trait Solver {
+    /// The parsed puzzle input. It is shared between threads when the parts
+    /// are solved concurrently.
+    type Input: Send + Sync + 'static;
+
+    /// The day of the advent calendar this solver belongs to.
+    const DAY: u32;
+
+    /// The title of the puzzle.
+    const TITLE: &'static str;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, ParseError>;
+
+    fn part_one(&self, input: &Self::Input) -> Result<Answer, SolveError>;
+
+    fn part_two(&self, _input: &Self::Input) -> Result<Answer, SolveError> {
+        Err(SolveError::NotImplemented)
+    }
+}
+
+/// Object-safe version of [`Solver`], used to store the days in a registry.
+/// Implemented for every [`Solver`].
+pub trait Puzzle: Sync {
+    fn day(&self) -> u32;
+
+    fn title(&self) -> &'static str;
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Any + Send + Sync>, ParseError>;
+
+    /// Panics if `input` was not created by [`Puzzle::parse`] on the same puzzle.
+    fn part_one(&self, input: &dyn Any) -> Result<Answer, SolveError>;
+
+    /// Panics if `input` was not created by [`Puzzle::parse`] on the same puzzle.
+    fn part_two(&self, input: &dyn Any) -> Result<Answer, SolveError>;
+
+    /// Solves the given part, see [`Puzzle::part_one`] and [`Puzzle::part_two`].
+    fn solve(&self, input: &dyn Any, part: Part) -> Result<Answer, SolveError> {
+        match part {
+            Part::One => self.part_one(input),
+            Part::Two => self.part_two(input),
+        }
+    }
+
+    /// The path of the full puzzle input.
+    fn input_path(&self) -> PathBuf {
+        PathBuf::from(format!("src/d{}/full_input.dat", self.day()))
+    }
+}
+
+impl<S: Solver + Sync> Puzzle for S {
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        S::TITLE
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Any + Send + Sync>, ParseError> {
+        Ok(Box::new(Solver::parse(self, input)?))
+    }
+
+    fn part_one(&self, input: &dyn Any) -> Result<Answer, SolveError> {
+        Solver::part_one(self, downcast::<S>(input))
+    }
+
+    fn part_two(&self, input: &dyn Any) -> Result<Answer, SolveError> {
+        Solver::part_two(self, downcast::<S>(input))
+    }
+}
+
+/// A puzzle input parsed once, to solve any number of parts with, and how
+/// long parsing took.
+pub struct ParsedInput {
+    pub input: Result<Box<dyn Any + Send + Sync>, ParseError>,
+    pub duration: Duration,
+}
+
+impl ParsedInput {
+    /// Parses `input` for `puzzle`, timing it.
+    pub fn new(puzzle: &dyn Puzzle, input: &str) -> Self {
+        let now = Instant::now();
+        let input = puzzle.parse(input);
+        ParsedInput {
+            input,
+            duration: now.elapsed(),
+        }
+    }
+
+    /// Solves `part` with the parsed input, timing it. `puzzle` must be the
+    /// puzzle that parsed the input. If the input could not be parsed, the
+    /// parse error is the result.
+    pub fn solve(&self, puzzle: &dyn Puzzle, part: Part) -> PartRun {
+        match &self.input {
+            Ok(input) => {
+                let now = Instant::now();
+                let result = puzzle.solve(input.as_ref(), part);
+                PartRun {
+                    part,
+                    parse_duration: self.duration,
+                    solve_duration: Some(now.elapsed()),
+                    result,
+                }
+            }
+            Err(err) => PartRun {
+                part,
+                parse_duration: self.duration,
+                solve_duration: None,
+                result: Err(SolveError::from(err.clone())),
+            },
+        }
+    }
+}
+
+/// The result of solving one part of a puzzle, and how long it took. Made by
+/// [`ParsedInput::solve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartRun {
+    pub part: Part,
+    /// The time spent parsing the input. The input is parsed once for all
+    /// parts of a run, so they share this duration.
+    pub parse_duration: Duration,
+    /// The time spent solving the part, or `None` if the input could not be
+    /// parsed.
+    pub solve_duration: Option<Duration>,
+    pub result: Result<Answer, SolveError>,
+}
+
+/// Parses `input` once and solves each of `parts` with it, timing both steps.
+/// If the input can not be parsed, the parse error is the result of every part.
+pub fn run_parts(puzzle: &dyn Puzzle, input: &str, parts: &[Part]) -> Vec<PartRun> {
+    let parsed = ParsedInput::new(puzzle, input);
+    parts
+        .iter()
+        .map(|&part| parsed.solve(puzzle, part))
+        .collect()
+}
+
+fn downcast<S: Solver>(input: &dyn Any) -> &S::Input {
+    input
+        .downcast_ref()
+        .unwrap_or_else(|| panic!("Input was not parsed by day {}!", S::DAY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d9::Day9;
+
+    #[test]
+    fn large_answers() {
+        assert_eq!(answer_from_u64(42), Ok(42));
+        assert_eq!(answer_from_u64(i64::MAX as u64), Ok(i64::MAX));
+        assert!(matches!(
+            answer_from_u64(i64::MAX as u64 + 1),
+            Err(SolveError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn run_parts_parses_once() {
+        let runs = run_parts(&Day9, "0 3 6 9 12 15", &Part::BOTH);
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].result, Ok(18));
+        assert_eq!(runs[1].result, Err(SolveError::NotImplemented));
+        assert_eq!(runs[0].parse_duration, runs[1].parse_duration);
+        assert!(runs.iter().all(|run| run.solve_duration.is_some()));
+    }
+
+    #[test]
+    fn parts_share_a_parse_across_threads() {
+        let parsed = ParsedInput::new(&Day9, "0 3 6 9 12 15");
+        let runs = crate::pool::map(&Part::BOTH, 2, |&part| parsed.solve(&Day9, part));
+        assert_eq!(runs[0].result, Ok(18));
+        assert_eq!(runs[1].result, Err(SolveError::NotImplemented));
+        assert!(runs.iter().all(|run| run.parse_duration == parsed.duration));
+    }
+
+    #[test]
+    fn run_parts_shares_parse_errors() {
+        let runs = run_parts(&Day9, "0 3 x", &Part::BOTH);
+        for run in runs {
+            assert!(matches!(run.result, Err(SolveError::Parse(_))));
+            assert_eq!(run.solve_duration, None);
+        }
+    }
+}
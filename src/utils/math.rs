@@ -0,0 +1,384 @@
+//! Number theory: greatest common divisors, least common multiples, modular
+//! inverses and the Chinese remainder theorem, for `u64` and `u128`.
+
+/// The greatest common divisor of `a` and `b`, with `gcd(0, 0) = 0`.
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The least common multiple of `a` and `b`. Panics if it does not fit in a
+/// `u64`, see [`checked_lcm`].
+pub fn lcm(a: u64, b: u64) -> u64 {
+    checked_lcm(a, b).unwrap_or_else(|| panic!("lcm({}, {}) overflows", a, b))
+}
+
+/// The least common multiple of `a` and `b`, or `None` if it does not fit in
+/// a `u64`. The least common multiple of 0 and anything is 0.
+pub fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+pub fn checked_lcm_u128(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd_u128(a, b)).checked_mul(b)
+}
+
+/// The least common multiple of every number, 1 if there are none, or `None`
+/// if it does not fit in a `u64`.
+pub fn checked_lcm_of(numbers: &[u64]) -> Option<u64> {
+    numbers
+        .iter()
+        .try_fold(1, |multiple, &number| checked_lcm(multiple, number))
+}
+
+/// Finds `(g, x, y)` such that `g = gcd(a, b) = a * x + b * y`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The `x` in `0..modulus` with `a * x = 1 (mod modulus)`, or `None` if `a`
+/// and `modulus` are not coprime.
+pub fn mod_inverse(a: u64, modulus: u64) -> Option<u64> {
+    if modulus == 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a as i128, modulus as i128);
+    if g != 1 {
+        return None;
+    }
+    Some(x.rem_euclid(modulus as i128) as u64)
+}
+
+/// `(a + b) % modulus` for `a` and `b` below `modulus`, without overflowing.
+fn add_mod_u128(a: u128, b: u128, modulus: u128) -> u128 {
+    if a >= modulus - b {
+        a - (modulus - b)
+    } else {
+        a + b
+    }
+}
+
+/// `(a - b) % modulus` for `a` and `b` below `modulus`, without going negative.
+fn sub_mod_u128(a: u128, b: u128, modulus: u128) -> u128 {
+    if a >= b {
+        a - b
+    } else {
+        a + (modulus - b)
+    }
+}
+
+/// `a * b % modulus`, without overflowing, by doubling and adding.
+fn mul_mod_u128(a: u128, mut b: u128, modulus: u128) -> u128 {
+    let mut a = a % modulus;
+    let mut product = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            product = add_mod_u128(product, a, modulus);
+        }
+        a = add_mod_u128(a, a, modulus);
+        b >>= 1;
+    }
+    product
+}
+
+/// [`mod_inverse`] for `u128`. The coefficients are kept modulo `modulus`, so
+/// nothing overflows.
+pub fn mod_inverse_u128(a: u128, modulus: u128) -> Option<u128> {
+    if modulus == 0 {
+        return None;
+    }
+
+    // old_r = old_x * a and r = x * a (mod modulus) throughout
+    let (mut old_r, mut r) = (a % modulus, modulus);
+    let (mut old_x, mut x) = (1 % modulus, 0);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (
+            x,
+            sub_mod_u128(old_x, mul_mod_u128(quotient, x, modulus), modulus),
+        );
+    }
+
+    (old_r == 1).then_some(old_x)
+}
+
+/// A set of numbers, `x = residue (mod modulus)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Congruence {
+    pub residue: u64,
+    pub modulus: u64,
+}
+
+impl Congruence {
+    /// Panics if `modulus` is 0.
+    pub fn new(residue: u64, modulus: u64) -> Self {
+        assert!(modulus > 0, "the modulus of a congruence can not be 0");
+        Congruence {
+            residue: residue % modulus,
+            modulus,
+        }
+    }
+
+    pub fn contains(&self, x: u64) -> bool {
+        x % self.modulus == self.residue
+    }
+
+    /// The smallest number in the set that is at least `min`, or `None` if it
+    /// does not fit in a `u64`.
+    pub fn first_from(&self, min: u64) -> Option<u64> {
+        // in u128, as the sum can overflow a u64 for moduli above 2^63
+        let modulus = self.modulus as u128;
+        let below = ((min % self.modulus) as u128 + modulus - self.residue as u128) % modulus;
+        match below {
+            0 => Some(min),
+            _ => min.checked_add((modulus - below) as u64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrtError {
+    /// No number satisfies every congruence.
+    NoSolution,
+    /// The combined modulus does not fit in a `u64`, or a `u128` for
+    /// [`crt_u128`].
+    Overflow,
+}
+
+/// Combines congruences into one that holds exactly when all of them do, with
+/// the generalized Chinese remainder theorem. The moduli do not have to be
+/// coprime. No congruences give `x = 0 (mod 1)`.
+pub fn crt(congruences: &[Congruence]) -> Result<Congruence, CrtError> {
+    congruences
+        .iter()
+        .try_fold(Congruence::new(0, 1), |a, &b| crt_pair(a, b))
+}
+
+fn crt_pair(a: Congruence, b: Congruence) -> Result<Congruence, CrtError> {
+    let (g, p, _) = extended_gcd(a.modulus as i128, b.modulus as i128);
+    let g = g as u64;
+    let (low, high) = (a.residue.min(b.residue), a.residue.max(b.residue));
+    if (high - low) % g != 0 {
+        return Err(CrtError::NoSolution);
+    }
+
+    // x = a.residue + a.modulus * k, where a.modulus * k = b.residue - a.residue
+    // (mod b.modulus), and p is the inverse of a.modulus / g modulo b.modulus / g
+    let reduced = (b.modulus / g) as u128;
+    let difference =
+        (b.residue as i128 - a.residue as i128).rem_euclid(b.modulus as i128) as u128 / g as u128;
+    let inverse = p.rem_euclid(reduced as i128) as u128;
+    let k = difference % reduced * inverse % reduced;
+
+    let modulus = checked_lcm(a.modulus, b.modulus).ok_or(CrtError::Overflow)?;
+    let residue = (a.residue as u128 + a.modulus as u128 * k) % modulus as u128;
+    Ok(Congruence::new(residue as u64, modulus))
+}
+
+/// [`crt`] for congruences given as `(residue, modulus)` pairs of `u128`s, for
+/// when the combined modulus does not fit in a `u64`. Panics if a modulus is 0.
+pub fn crt_u128(congruences: &[(u128, u128)]) -> Result<(u128, u128), CrtError> {
+    congruences
+        .iter()
+        .try_fold((0, 1), |a, &b| crt_pair_u128(a, b))
+}
+
+fn crt_pair_u128(
+    (a_residue, a_modulus): (u128, u128),
+    (b_residue, b_modulus): (u128, u128),
+) -> Result<(u128, u128), CrtError> {
+    assert!(b_modulus > 0, "the modulus of a congruence can not be 0");
+    let g = gcd_u128(a_modulus, b_modulus);
+    let (a_residue, b_residue) = (a_residue % a_modulus, b_residue % b_modulus);
+    let difference = sub_mod_u128(b_residue, a_residue % b_modulus, b_modulus);
+    if !difference.is_multiple_of(g) {
+        return Err(CrtError::NoSolution);
+    }
+
+    // as in crt_pair, but the product a_modulus * k is below the combined
+    // modulus, so it can only overflow if the modulus does
+    let reduced = b_modulus / g;
+    let inverse = mod_inverse_u128(a_modulus / g, reduced).expect("a_modulus / g is coprime");
+    let k = mul_mod_u128(difference / g, inverse, reduced);
+
+    let modulus = checked_lcm_u128(a_modulus, b_modulus).ok_or(CrtError::Overflow)?;
+    Ok((a_residue + a_modulus * k, modulus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(gcd_u128(1 << 100, 3 << 90), 1 << 90);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(checked_lcm(0, 5), Some(0));
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(
+            checked_lcm_u128(u64::MAX as u128, 2),
+            Some(2 * u64::MAX as u128)
+        );
+    }
+
+    #[test]
+    fn lcm_of_large_coprimes() {
+        // far too many steps for a search that steps through the multiples
+        let primes = [1_000_003, 1_000_033, 1_000_037];
+        assert_eq!(
+            checked_lcm_of(&primes),
+            Some(1_000_003 * 1_000_033 * 1_000_037)
+        );
+        assert_eq!(checked_lcm_of(&[]), Some(1));
+        assert_eq!(checked_lcm_of(&[u64::MAX, 2]), None);
+    }
+
+    #[test]
+    fn extended_gcd_and_inverse() {
+        for (a, b) in [(240, 46), (-240, 46), (17, 0), (0, -5), (1 << 80, 3 << 70)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert!(g >= 0);
+            assert_eq!(a * x + b * y, g);
+        }
+
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(10, 17), Some(12));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_inverse(5, 1), Some(0));
+
+        assert_eq!(mod_inverse_u128(10, 17), Some(12));
+        assert_eq!(mod_inverse_u128(6, 9), None);
+        assert_eq!(mod_inverse_u128(5, 1), Some(0));
+        assert_eq!(mod_inverse_u128(0, 7), None);
+        for (a, modulus) in [
+            (2, u128::MAX),
+            (u128::MAX - 1, u128::MAX),
+            (1 << 100, (1 << 127) - 1),
+        ] {
+            let inverse = mod_inverse_u128(a, modulus).unwrap();
+            assert_eq!(mul_mod_u128(a, inverse, modulus), 1);
+        }
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        let c = Congruence::new;
+        assert_eq!(crt(&[c(2, 3), c(3, 5), c(2, 7)]), Ok(c(23, 105)));
+        // non-coprime moduli
+        assert_eq!(crt(&[c(1, 4), c(3, 6)]), Ok(c(9, 12)));
+        assert_eq!(crt(&[c(1, 4), c(2, 6)]), Err(CrtError::NoSolution));
+        assert_eq!(crt(&[c(0, 6), c(0, 10), c(0, 15)]), Ok(c(0, 30)));
+        assert_eq!(crt(&[]), Ok(c(0, 1)));
+        assert_eq!(
+            crt(&[c(1, u64::MAX), c(0, u64::MAX - 1)]),
+            Err(CrtError::Overflow)
+        );
+
+        // moduli whose least common multiple only just fits
+        let (m1, m2) = (u32::MAX as u64 * 2 + 1, i32::MAX as u64);
+        let combined = crt(&[c(12345, m1), c(678, m2)]).unwrap();
+        assert!(c(12345, m1).contains(combined.residue));
+        assert!(c(678, m2).contains(combined.residue));
+    }
+
+    #[test]
+    fn agrees_with_brute_force() {
+        for m1 in 1..13 {
+            for m2 in 1..13 {
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let (a, b) = (Congruence::new(r1, m1), Congruence::new(r2, m2));
+                        let expected = (0..lcm(m1, m2)).find(|&x| a.contains(x) && b.contains(x));
+                        let result = crt(&[a, b]).ok().map(|c| c.residue);
+                        assert_eq!(result, expected, "{:?} and {:?}", a, b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn first_from() {
+        let c = Congruence::new(3, 7);
+        assert_eq!(c.first_from(0), Some(3));
+        assert_eq!(c.first_from(3), Some(3));
+        assert_eq!(c.first_from(4), Some(10));
+        assert_eq!(Congruence::new(0, 5).first_from(10), Some(10));
+        assert_eq!(c.first_from(u64::MAX - 1), None);
+
+        // moduli above 2^63
+        assert_eq!(
+            Congruence::new(0, u64::MAX).first_from(u64::MAX - 1),
+            Some(u64::MAX)
+        );
+        assert_eq!(Congruence::new(1, u64::MAX).first_from(u64::MAX - 1), None);
+        assert_eq!(
+            Congruence::new(5, 1 << 63).first_from(1 << 63),
+            Some((1 << 63) + 5)
+        );
+    }
+
+    #[test]
+    fn chinese_remainders_u128() {
+        assert_eq!(crt_u128(&[(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        assert_eq!(crt_u128(&[(1, 4), (2, 6)]), Err(CrtError::NoSolution));
+        assert_eq!(crt_u128(&[]), Ok((0, 1)));
+
+        // the combined modulus only fits in a u128
+        let (m1, m2) = (u64::MAX as u128, u64::MAX as u128 - 1);
+        let (residue, modulus) = crt_u128(&[(1, m1), (0, m2)]).unwrap();
+        assert_eq!(modulus, m1 * m2);
+        assert_eq!((residue % m1, residue % m2), (1, 0));
+        assert_eq!(
+            crt_u128(&[(0, u128::MAX), (0, u128::MAX - 1)]),
+            Err(CrtError::Overflow)
+        );
+
+        for m1 in 1..13 {
+            for m2 in 1..13 {
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let expected = crt(&[Congruence::new(r1, m1), Congruence::new(r2, m2)])
+                            .map(|c| (c.residue as u128, c.modulus as u128));
+                        let pairs = [(r1 as u128, m1 as u128), (r2 as u128, m2 as u128)];
+                        assert_eq!(crt_u128(&pairs), expected);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,26 +1,32 @@
-use std::fmt::Debug;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::iter::Flatten;
+use std::fs;
 use std::path::Path;
 use std::str::FromStr;
 
-pub fn read_lines<T: AsRef<Path>>(filename: T) -> Flatten<std::io::Lines<BufReader<File>>> {
-    let file = File::open(filename).unwrap();
-    BufReader::new(file).lines().flatten()
+use crate::error::{ParseError, ParseErrorKind};
+
+pub mod math;
+pub mod range_set;
+
+pub use range_set::{LinearPiece, RangeSet};
+
+/// Reads a whole input file, panicking if it can not be read.
+pub fn read_input<T: AsRef<Path>>(filename: T) -> String {
+    let filename = filename.as_ref();
+    fs::read_to_string(filename)
+        .unwrap_or_else(|err| panic!("Could not read {:?}: {}", filename, err))
 }
 
 /// Creates an iterator that iterates over the parsed values in a string.
 /// String is split using 'pattern', and the first 'skip' values are skipped
-/// before parsing.
+/// before parsing. Errors point into 'string', use [`ParseError::within`] to
+/// place them in the full input.
 pub fn string_to_iter<'a, T>(
     string: &'a str,
     pattern: &'a str,
     skip: usize,
-) -> impl Iterator<Item = T> + 'a
+) -> impl Iterator<Item = Result<T, ParseError>> + 'a
 where
     T: FromStr,
-    <T as FromStr>::Err: Debug,
 {
     string
         .split(pattern)
@@ -28,17 +34,16 @@ where
         .skip(skip)
         .map(|c| {
             c.parse()
-                .unwrap_or_else(|_| panic!("Could not parse string: {:?}", c))
+                .map_err(|_| ParseError::at(string, c, ParseErrorKind::InvalidNumber))
         })
 }
 
 /// Creates a vector that contains the parsed values in a string.
 /// String is split using 'pattern', and the first 'skip' values are skipped
 /// before parsing.
-pub fn string_to_array<T>(string: &str, pattern: &str, skip: usize) -> Vec<T>
+pub fn string_to_array<T>(string: &str, pattern: &str, skip: usize) -> Result<Vec<T>, ParseError>
 where
     T: FromStr,
-    <T as FromStr>::Err: Debug,
 {
     string_to_iter(string, pattern, skip).collect()
 }
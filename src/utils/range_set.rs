@@ -0,0 +1,335 @@
+use std::fmt;
+use std::ops::{Add, Range, Sub};
+
+/// A set of values stored as sorted, disjoint, half-open ranges.
+///
+/// The ranges are kept normalized: none of them are empty, and overlapping or
+/// touching ranges are merged, so two sets with the same values always have
+/// the same ranges.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct RangeSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+/// One piece of a piecewise-linear function, moving every value in its source
+/// range by the same offset. See [`RangeSet::map_through`].
+pub trait LinearPiece<T> {
+    /// The values this piece maps.
+    fn source(&self) -> Range<T>;
+
+    /// The value that the start of the source range is mapped to.
+    fn destination_start(&self) -> T;
+}
+
+impl<T> Default for RangeSet<T> {
+    fn default() -> Self {
+        RangeSet { ranges: vec![] }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RangeSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.ranges.iter()).finish()
+    }
+}
+
+impl<T: Copy + Ord> RangeSet<T> {
+    pub fn new() -> Self {
+        RangeSet::default()
+    }
+
+    /// The ranges of the set, sorted and disjoint.
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Range<T>> {
+        self.ranges.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The smallest value in the set.
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|range| range.end <= value);
+        self.ranges
+            .get(i)
+            .is_some_and(|range| range.contains(&value))
+    }
+
+    /// Adds every value in `range` to the set.
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // the ranges that overlap or touch the new one
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+
+        let merged = if first == last {
+            range
+        } else {
+            self.ranges[first].start.min(range.start)..self.ranges[last - 1].end.max(range.end)
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.iter().chain(other.iter()).cloned().collect()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let overlap = a.start.max(b.start)..a.end.min(b.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+
+            // whichever range ends first can not overlap anything else
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        // the overlaps are disjoint and sorted, but may touch
+        ranges.into_iter().collect()
+    }
+
+    /// The values in `self` that are not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let mut j = 0;
+
+        for range in self.iter() {
+            let mut start = range.start;
+
+            // skip the ranges of other that end before this range
+            while other.ranges.get(j).is_some_and(|b| b.end <= start) {
+                j += 1;
+            }
+
+            let mut k = j;
+            while let Some(b) = other.ranges.get(k).filter(|b| b.start < range.end) {
+                if start < b.start {
+                    ranges.push(start..b.start);
+                }
+                start = start.max(b.end);
+                k += 1;
+            }
+
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+
+        RangeSet { ranges }
+    }
+}
+
+impl<T> RangeSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T>,
+{
+    /// Maps every value in the set through a piecewise-linear function. Values
+    /// covered by several pieces are mapped by the first of them, and values
+    /// covered by none are left as they are.
+    pub fn map_through<P: LinearPiece<T>>(&self, pieces: &[P]) -> Self {
+        let mut mapped = RangeSet::new();
+        let mut unmapped = self.clone();
+
+        for piece in pieces {
+            let source = piece.source();
+            let covered = unmapped.intersection(&RangeSet::from(source.clone()));
+            if covered.is_empty() {
+                continue;
+            }
+
+            let destination = piece.destination_start();
+            for range in covered.iter() {
+                mapped.insert(
+                    (range.start - source.start + destination)
+                        ..(range.end - source.start + destination),
+                );
+            }
+            unmapped = unmapped.difference(&covered);
+        }
+
+        mapped.union(&unmapped)
+    }
+}
+
+impl<T: Copy + Ord> From<Range<T>> for RangeSet<T> {
+    fn from(range: Range<T>) -> Self {
+        let mut set = RangeSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Copy + Ord> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut ranges: Vec<Range<T>> = iter.into_iter().filter(|r| !r.is_empty()).collect();
+        ranges.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        RangeSet { ranges: merged }
+    }
+}
+
+impl<T: Copy + Ord> Extend<Range<T>> for RangeSet<T> {
+    fn extend<I: IntoIterator<Item = Range<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RangeSet<T> {
+    type Item = &'a Range<T>;
+    type IntoIter = std::slice::Iter<'a, Range<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)] // the arrays are sets of ranges
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[Range<i32>]) -> RangeSet<i32> {
+        ranges.iter().cloned().collect()
+    }
+
+    struct Piece(Range<i32>, i32);
+
+    impl LinearPiece<i32> for Piece {
+        fn source(&self) -> Range<i32> {
+            self.0.clone()
+        }
+
+        fn destination_start(&self) -> i32 {
+            self.1
+        }
+    }
+
+    #[test]
+    fn collect_normalizes() {
+        let set = set(&[5..8, 0..2, 7..10, 2..3, 4..4, 12..13]);
+        assert_eq!(set.ranges(), &[0..3, 5..10, 12..13]);
+    }
+
+    #[test]
+    fn insert_merges() {
+        let mut set = set(&[0..2, 4..6, 8..10, 12..14]);
+        set.insert(5..9);
+        assert_eq!(set.ranges(), &[0..2, 4..10, 12..14]);
+        set.insert(2..4);
+        assert_eq!(set.ranges(), &[0..10, 12..14]);
+        set.insert(16..18);
+        set.insert(-3..-1);
+        set.insert(20..20);
+        assert_eq!(set.ranges(), &[-3..-1, 0..10, 12..14, 16..18]);
+        set.insert(-5..30);
+        assert_eq!(set.ranges(), &[-5..30]);
+    }
+
+    #[test]
+    fn insert_agrees_with_collect() {
+        let ranges = [3..9, 20..25, 0..1, 9..11, 15..21, 1..2, 30..31, 24..30];
+        for n in 0..=ranges.len() {
+            let mut inserted = RangeSet::new();
+            inserted.extend(ranges[..n].iter().cloned());
+            assert_eq!(inserted, set(&ranges[..n]));
+        }
+    }
+
+    #[test]
+    fn contains() {
+        let set = set(&[0..3, 5..10]);
+        let values: Vec<i32> = (-2..12).filter(|&v| set.contains(v)).collect();
+        assert_eq!(values, [0, 1, 2, 5, 6, 7, 8, 9]);
+        assert!(!RangeSet::new().contains(0));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[0..5, 10..15, 20..25]);
+        let b = set(&[3..12, 14..21, 30..31]);
+
+        assert_eq!(a.union(&b).ranges(), &[0..25, 30..31]);
+        assert_eq!(a.intersection(&b).ranges(), &[3..5, 10..12, 14..15, 20..21]);
+        assert_eq!(a.difference(&b).ranges(), &[0..3, 12..14, 21..25]);
+        assert_eq!(b.difference(&a).ranges(), &[5..10, 15..20, 30..31]);
+    }
+
+    #[test]
+    fn set_operations_agree_with_values() {
+        let sets = [
+            set(&[]),
+            set(&[0..20]),
+            set(&[0..5, 10..15, 20..25]),
+            set(&[3..12, 14..21, 30..31]),
+            set(&[-4..1, 4..5, 6..7, 9..10, 24..40]),
+        ];
+
+        for a in sets.iter() {
+            for b in sets.iter() {
+                for value in -5..45 {
+                    let (in_a, in_b) = (a.contains(value), b.contains(value));
+                    assert_eq!(a.union(b).contains(value), in_a || in_b);
+                    assert_eq!(a.intersection(b).contains(value), in_a && in_b);
+                    assert_eq!(a.difference(b).contains(value), in_a && !in_b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn intersection_merges_touching_overlaps() {
+        let a = set(&[0..10]);
+        let b = set(&[0..5, 5..10]);
+        assert_eq!(a.intersection(&b).ranges(), &[0..10]);
+    }
+
+    #[test]
+    fn map_through() {
+        let pieces = [Piece(98..100, 50), Piece(50..98, 52)];
+
+        assert_eq!(set(&[79..93]).map_through(&pieces).ranges(), &[81..95]);
+        assert_eq!(
+            set(&[40..60, 95..105]).map_through(&pieces).ranges(),
+            &[40..62, 97..105]
+        );
+        assert!(RangeSet::new().map_through(&pieces).is_empty());
+    }
+
+    #[test]
+    fn map_through_uses_first_piece() {
+        let pieces = [Piece(0..10, 100), Piece(5..15, 200)];
+        assert_eq!(
+            set(&[0..15]).map_through(&pieces).ranges(),
+            &[100..110, 205..210]
+        );
+    }
+}
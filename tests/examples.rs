@@ -0,0 +1,86 @@
+//! Runs every day on the example inputs found in `src/dN/test_input*.dat`,
+//! and checks the results against the answers in `answers.toml`.
+
+use std::fs;
+use std::path::PathBuf;
+
+use aoc2023::manifest::{Manifest, Status, DEFAULT_MANIFEST_PATH};
+use aoc2023::{pool, DAYS};
+
+/// Finds every example input, along with the day it belongs to.
+fn discover_examples() -> Vec<(u32, PathBuf)> {
+    let mut examples = vec![];
+    for entry in fs::read_dir("src").unwrap() {
+        let dir = entry.unwrap().path();
+        let Some(day) = dir
+            .file_name()
+            .and_then(|name| name.to_str()?.strip_prefix('d')?.parse().ok())
+        else {
+            continue;
+        };
+
+        for entry in fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy();
+            if name.starts_with("test_input") && name.ends_with(".dat") {
+                examples.push((day, path));
+            }
+        }
+    }
+
+    examples.sort();
+    examples
+}
+
+#[test]
+fn every_day_has_an_example() {
+    let examples = discover_examples();
+    for puzzle in DAYS {
+        assert!(
+            examples.iter().any(|(day, _)| *day == puzzle.day()),
+            "day {} has no test_input*.dat",
+            puzzle.day()
+        );
+    }
+}
+
+#[test]
+fn examples_match_the_manifest() {
+    let manifest = Manifest::load(DEFAULT_MANIFEST_PATH).unwrap();
+
+    let mut answers = vec![];
+    for (day, path) in discover_examples() {
+        let expected: Vec<_> = manifest
+            .answers
+            .iter()
+            .filter(|answer| answer.day == day && answer.input == path)
+            .cloned()
+            .collect();
+        assert!(
+            !expected.is_empty(),
+            "{} has no expected answer in {}",
+            path.display(),
+            DEFAULT_MANIFEST_PATH
+        );
+        answers.extend(expected);
+    }
+
+    let examples = Manifest { answers };
+    let failures: Vec<String> = examples
+        .verify(pool::default_workers())
+        .into_iter()
+        .filter(|verification| verification.status != Status::Pass)
+        .filter(|verification| verification.status != Status::NotSolved)
+        .map(|verification| {
+            let expected = &verification.expected;
+            format!(
+                "day {} part {} on {}: {:?}",
+                expected.day,
+                expected.part.number(),
+                expected.input.display(),
+                verification.status
+            )
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}